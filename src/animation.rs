@@ -0,0 +1,138 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+use crate::grid::Grid;
+use crate::render::{save, write_ppm, Rgb};
+
+/// One captured frame, as listed in the manifest.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Frame {
+    pub step: usize,
+    pub file: String,
+    pub width: usize,
+    pub height: usize
+}
+
+/// Records a simulation as a numbered sequence of PPM files in `dir/name/`, capturing a frame
+/// every `every` steps until `max_frames` have been written. Frames go straight to disk, so
+/// only their manifest entries are kept in memory.
+pub struct Recorder {
+    dir: PathBuf,
+    every: usize,
+    max_frames: usize,
+    scale: usize,
+    steps: usize,
+    frames: Vec<Frame>
+}
+
+impl Recorder {
+    /// A recorder capturing every step, at scale 1, for at most 1000 frames.
+    pub fn new(dir: impl Into<PathBuf>, name: &str) -> Recorder {
+        Recorder {
+            dir: dir.into().join(name),
+            every: 1,
+            max_frames: 1000,
+            scale: 1,
+            steps: 0,
+            frames: Vec::new()
+        }
+    }
+
+    pub fn every(mut self, steps: usize) -> Recorder {
+        assert!(steps > 0, "frames must be at least one step apart");
+        self.every = steps;
+        self
+    }
+
+    pub fn max_frames(mut self, max_frames: usize) -> Recorder {
+        self.max_frames = max_frames;
+        self
+    }
+
+    pub fn scale(mut self, scale: usize) -> Recorder {
+        self.scale = scale;
+        self
+    }
+
+    /// The number of steps seen so far.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.frames.len() >= self.max_frames
+    }
+
+    /// Whether the current step will be captured.
+    pub fn is_due(&self) -> bool {
+        self.steps.is_multiple_of(self.every) && !self.is_full()
+    }
+
+    /// Captures the current state if a frame is due, then counts one step. `frame` is only
+    /// called when its result will be used.
+    pub fn step<T>(&mut self, frame: impl FnOnce() -> Grid<T>, colour: impl Fn(&T) -> Rgb) -> io::Result<()> {
+        if self.is_due() {
+            self.capture(&frame(), colour)?;
+        }
+
+        self.steps += 1;
+        Ok(())
+    }
+
+    /// Captures `grid` regardless of the step, unless the frame cap has been reached.
+    pub fn capture<T>(&mut self, grid: &Grid<T>, colour: impl Fn(&T) -> Rgb) -> io::Result<()> {
+        if self.is_full() {
+            return Ok(());
+        }
+
+        let file = format!("frame-{:05}.ppm", self.frames.len());
+        save(self.dir.join(&file), |out| write_ppm(out, grid, self.scale, colour))?;
+
+        self.frames.push(Frame {
+            step: self.steps,
+            file,
+            width: grid.width() * self.scale,
+            height: grid.height() * self.scale
+        });
+        Ok(())
+    }
+
+    /// Writes `manifest.txt`, one tab-separated line per frame, and returns the frames.
+    pub fn finish(self) -> io::Result<Vec<Frame>> {
+        save(self.dir.join("manifest.txt"), |out| {
+            writeln!(out, "frame\tstep\twidth\theight\tfile")?;
+            for (i, frame) in self.frames.iter().enumerate() {
+                writeln!(out, "{i}\t{}\t{}\t{}\t{}", frame.step, frame.width, frame.height, frame.file)?;
+            }
+            Ok(())
+        })?;
+
+        Ok(self.frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_recorder() {
+        let dir = std::env::temp_dir().join(format!("animation-test-{}", std::process::id()));
+        let mut recorder = Recorder::new(&dir, "counter").every(3).max_frames(2).scale(2);
+
+        for n in 0..10 {
+            recorder.step(|| Grid::new(n + 1, 1, n), |&v| Rgb::gray(v as u8)).unwrap();
+        }
+
+        let frames = recorder.finish().unwrap();
+        assert_eq!(frames.iter().map(|f| f.step).collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!((frames[1].width, frames[1].height), (8, 2));
+
+        let manifest = fs::read_to_string(dir.join("counter/manifest.txt")).unwrap();
+        assert_eq!(manifest.lines().nth(2), Some("1\t3\t8\t2\tframe-00001.ppm"));
+        assert!(fs::read(dir.join("counter/frame-00001.ppm")).unwrap().starts_with(b"P6\n8 2\n255\n"));
+        assert!(!dir.join("counter/frame-00002.ppm").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,33 +1,247 @@
-use itertools::Itertools;
-use adventofcode2017::build_main;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use adventofcode2017::build_main_res;
+use adventofcode2017::math::circular_autocorrelation;
+use adventofcode2017::render::save;
 
-fn part1(input: &str) -> usize {
-    let mut vals = input.chars()
-        .map(|c| c.to_digit(10).unwrap() as usize)
-        .collect_vec();
+/// The digits of `input`, ignoring trailing whitespace, or an error naming the first character
+/// that is not a digit.
+fn parse_digits(input: &str) -> Result<Vec<usize>, String> {
+    input.trim_end().chars().enumerate()
+        .map(|(i, c)| {
+            c.to_digit(10)
+                .map(|d| d as usize)
+                .ok_or_else(|| format!("invalid digit {c:?} at position {i}"))
+        })
+        .collect()
+}
 
-    vals.push(vals[0]);
+/// The sum of the digits that match the digit `offset` places further round the circle.
+fn captcha(digits: &[usize], offset: usize) -> usize {
+    let n = digits.len();
 
-    vals.into_iter()
-        .tuple_windows()
-        .filter(|&(a, b)| a == b)
-        .map(|(a, _)| a)
+    (0..n)
+        .filter(|&i| digits[i] == digits[(i + offset) % n])
+        .map(|i| digits[i])
         .sum()
 }
 
-fn part2(input: &str) -> usize {
-    let vals = input.chars()
-        .map(|c| c.to_digit(10).unwrap() as usize)
-        .collect_vec();
+/// `captcha` for every offset at once, indexed by offset modulo the number of digits. Only
+/// equal digits can match, so the total at offset `k` is the sum over each digit value of that
+/// value times the circular autocorrelation, at `k`, of where it occurs; by FFT that is
+/// O(n log n) overall, where comparing every digit at every offset would be O(n^2).
+fn all_captchas(digits: &[usize]) -> Vec<usize> {
+    let mut result = vec![0; digits.len()];
 
-    let n = vals.len() / 2;
+    for d in (1..10).filter(|d| digits.contains(d)) {
+        let signal = digits.iter().map(|&x| if x == d { 1.0 } else { 0.0 }).collect::<Vec<_>>();
+        for (k, matches) in circular_autocorrelation(&signal).into_iter().enumerate() {
+            result[k] += d * matches.round() as usize;
+        }
+    }
 
+    result
+}
 
-    vals.iter().enumerate()
-        .map(|(i, x)| (*x, vals[(i + n) % vals.len()]))
-        .filter(|&(x, y)| x == y)
-        .map(|(x, _)| x)
-        .sum()
+/// Feeds each digit read from `reader` to `f`, a buffer at a time, ignoring whitespace.
+fn read_digits(reader: impl Read, mut f: impl FnMut(u8)) -> Result<(), String> {
+    let mut reader = BufReader::with_capacity(1 << 16, reader);
+    let mut position = 0;
+
+    loop {
+        let buf = reader.fill_buf().map_err(|e| e.to_string())?;
+        if buf.is_empty() {
+            return Ok(());
+        }
+
+        for &b in buf.iter() {
+            match b {
+                b'0'..=b'9' => f(b - b'0'),
+                _ if b.is_ascii_whitespace() => (),
+                _ => return Err(format!("invalid digit {:?} at position {position}", b as char))
+            }
+            position += 1;
+        }
+
+        let n = buf.len();
+        reader.consume(n);
+    }
+}
+
+/// `captcha` computed a digit at a time, holding only the last `offset` digits, in a ring,
+/// and the first `offset`, which the last ones wrap round to meet.
+struct StreamingCaptcha {
+    offset: usize,
+    head: Vec<u8>,
+    ring: Vec<u8>,
+    count: usize,
+    sum: usize,
+    halfway: bool
+}
+
+impl StreamingCaptcha {
+    fn new(offset: usize) -> StreamingCaptcha {
+        assert!(offset > 0, "a streaming captcha needs a positive offset");
+        StreamingCaptcha { offset, head: Vec::new(), ring: Vec::new(), count: 0, sum: 0, halfway: false }
+    }
+
+    /// The half-way captcha of exactly `len` digits, which must be even and positive. Half-way
+    /// round, the pairs that wrap match the pairs that don't, so the first digits need not be
+    /// kept.
+    fn halfway(len: usize) -> StreamingCaptcha {
+        assert!(len > 0 && len.is_multiple_of(2), "a half-way captcha needs a positive, even length");
+        StreamingCaptcha { halfway: true, ..StreamingCaptcha::new(len / 2) }
+    }
+
+    fn push(&mut self, digit: u8) {
+        if self.count < self.offset {
+            if !self.halfway {
+                self.head.push(digit);
+            }
+            self.ring.push(digit);
+        }
+        else {
+            let slot = self.count % self.offset;
+            if self.ring[slot] == digit {
+                self.sum += digit as usize;
+            }
+            self.ring[slot] = digit;
+        }
+        self.count += 1;
+    }
+
+    fn finish(self) -> Result<usize, String> {
+        let n = self.count;
+        let k = self.offset;
+
+        if self.halfway {
+            if n != 2 * k {
+                return Err(format!("expected {} digits, read {n}", 2 * k));
+            }
+            return Ok(2 * self.sum);
+        }
+
+        if n <= k {
+            // The offset goes round at least once, and every digit is still in `head`.
+            let digits = self.head.iter().map(|&d| d as usize).collect::<Vec<_>>();
+            return Ok(if n == 0 { 0 } else { captcha(&digits, k) });
+        }
+
+        let wrapped = (n - k..n)
+            .map(|i| (self.ring[i % k], self.head[i + k - n]))
+            .filter(|(a, b)| a == b)
+            .map(|(a, _)| a as usize)
+            .sum::<usize>();
+
+        Ok(self.sum + wrapped)
+    }
+}
+
+/// Both captchas in one pass over `reader`: part 1 in constant memory, and part 2, when told
+/// the number of digits, `len`, with a buffer of half that.
+fn stream_captchas(reader: &mut dyn Read, args: &[&str]) -> Result<Vec<(&'static str, String)>, String> {
+    let len = match args {
+        [] => None,
+        [len] => match len.parse::<usize>() {
+            Ok(len) if len.is_multiple_of(2) => Some(len),
+            Ok(len) => return Err(format!("the half-way captcha needs an even number of digits, not {len}")),
+            Err(_) => return Err(format!("invalid length {len:?}"))
+        },
+        _ => return Err("usage: stream [LENGTH]".to_string())
+    };
+
+    let mut next = StreamingCaptcha::new(1);
+    let mut halfway = len.filter(|&len| len > 0).map(StreamingCaptcha::halfway);
+    let mut count = 0;
+
+    read_digits(reader, |d| {
+        count += 1;
+        next.push(d);
+        if let Some(halfway) = halfway.as_mut() {
+            halfway.push(d);
+        }
+    })?;
+
+    let mut results = vec![("Part 1", next.finish()?.to_string())];
+    match (len, halfway) {
+        (_, Some(halfway)) => results.push(("Part 2", halfway.finish()?.to_string())),
+        (Some(_), None) if count == 0 => results.push(("Part 2", "0".to_string())),
+        (Some(_), None) => return Err(format!("expected 0 digits, read {count}")),
+        (None, _) => results.push(("Part 2", "needs the number of digits: stream LENGTH".to_string()))
+    }
+
+    Ok(results)
+}
+
+fn part1(input: &str) -> Result<usize, String> {
+    let digits = parse_digits(input)?;
+    Ok(captcha(&digits, 1))
+}
+
+fn part2(input: &str) -> Result<usize, String> {
+    let digits = parse_digits(input)?;
+    Ok(captcha(&digits, digits.len() / 2))
+}
+
+/// The captcha for every offset from 1 to the number of digits, one per line.
+fn write_offsets(input: &str, dir: &Path, _args: &[&str]) -> io::Result<()> {
+    let digits = parse_digits(input).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let captchas = all_captchas(&digits);
+
+    save(dir.join("day01-offsets.txt"), |out| {
+        for offset in 1..=digits.len() {
+            writeln!(out, "{offset}\t{}", captchas[offset % digits.len()])?;
+        }
+        Ok(())
+    })
 }
 
-build_main!("day01.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
+build_main_res!("day01.txt", "Part 1" => part1, "Part 2" => part2; render "offsets" => write_offsets; stream stream_captchas);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captcha() {
+        assert_eq!(part1("1122"), Ok(3));
+        assert_eq!(part1("91212129\n"), Ok(9));
+        assert_eq!(part2("123425"), Ok(4));
+        assert_eq!(part2("12131415"), Ok(4));
+        assert_eq!(part1("12a4"), Err("invalid digit 'a' at position 2".to_string()));
+
+        let digits = parse_digits("9121212912341234").unwrap();
+        let all = all_captchas(&digits);
+        assert!((0..digits.len()).all(|offset| all[offset] == captcha(&digits, offset)));
+
+        let digits = (0..5000u64).map(|i| (i * i * 7919 % 10007 % 10) as usize).collect::<Vec<_>>();
+        let all = all_captchas(&digits);
+        assert!([0, 1, 2, 999, 2500, 4999].iter().all(|&offset| all[offset] == captcha(&digits, offset)));
+
+        let dir = std::env::temp_dir().join(format!("day01-test-{}", std::process::id()));
+        write_offsets("1212", &dir, &[]).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("day01-offsets.txt")).unwrap(), "1\t0\n2\t6\n3\t0\n4\t6\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_streaming_captcha() {
+        let input = "91212129123412345599\n";
+        let digits = parse_digits(input).unwrap();
+
+        for offset in 1..=2 * digits.len() {
+            let mut streaming = StreamingCaptcha::new(offset);
+            read_digits(input.as_bytes(), |d| streaming.push(d)).unwrap();
+            assert_eq!(streaming.finish(), Ok(captcha(&digits, offset)), "offset {offset}");
+        }
+
+        let results = stream_captchas(&mut "123425".as_bytes(), &["6"]).unwrap();
+        assert_eq!(results, vec![("Part 1", "0".to_string()), ("Part 2", "4".to_string())]);
+
+        assert_eq!(stream_captchas(&mut "1234".as_bytes(), &["6"]), Err("expected 6 digits, read 4".to_string()));
+        assert_eq!(stream_captchas(&mut "".as_bytes(), &["0"]).unwrap()[1], ("Part 2", "0".to_string()));
+        assert_eq!(stream_captchas(&mut "12".as_bytes(), &["0"]), Err("expected 0 digits, read 2".to_string()));
+        assert!(stream_captchas(&mut "123".as_bytes(), &["3"]).unwrap_err().contains("even number of digits, not 3"));
+        assert_eq!(stream_captchas(&mut "12 x".as_bytes(), &[]), Err("invalid digit 'x' at position 3".to_string()));
+    }
+}
@@ -1,53 +1,278 @@
 use std::cmp::{max, min};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
 use itertools::Itertools;
-use nom::character::complete::{digit1, newline, space1};
-use nom::combinator::map_res;
+use nom::character::complete::space0;
+use nom::combinator::all_consuming;
 use nom::IResult;
-use nom::multi::separated_list1;
-use adventofcode2017::build_main;
+use nom::sequence::terminated;
+use adventofcode2017::build_main_res;
+use adventofcode2017::math::gcd;
+use adventofcode2017::parse::{fields, lines, unsigned};
+use adventofcode2017::render::save;
+use adventofcode2017::runner::VariantFn;
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Vec<usize>>> {
-    fn number(input: &str) -> IResult<&str, usize> {
-        map_res(digit1, |s: &str| s.parse::<usize>())(input)
-    }
+/// Rows of numbers separated by spaces, tabs or commas.
+fn parse_input(input: &str) -> Result<Vec<Vec<usize>>, String> {
+    let input = input.trim_end();
+    let result: IResult<&str, _> = all_consuming(lines(terminated(fields(unsigned), space0)))(input);
 
-    separated_list1(
-        newline,
-        separated_list1(
-            space1,
-            number
-        )
-    )(input)
+    result.map(|(_, rows)| rows).map_err(|e| {
+        let rest = match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => e.input,
+            nom::Err::Incomplete(_) => ""
+        };
+        let line = input[..input.len() - rest.len()].matches('\n').count() + 1;
+        format!("invalid spreadsheet row {line}: {:?}", input.lines().nth(line - 1).unwrap_or(""))
+    })
 }
 
-fn minmax(input: &[usize]) -> usize {
-    let (low, high) = input.iter()
+fn minmax(input: &[usize]) -> (usize, usize) {
+    input.iter()
         .fold((usize::MAX, 0), |(low, high), &next| {
             (min(low, next), max(high, next))
-        });
+        })
+}
+
+/// Every pair `(a, b)` of entries in `row` where `a` evenly divides `b` and `a <= b`, as
+/// `(a, b, count)` with `count` the number of such pairs of positions, smallest `a` first.
+/// Zeros are left out.
+fn divisible_pairs(row: &[usize]) -> Vec<(usize, usize, usize)> {
+    let counts = row.iter().copied().filter(|&v| v != 0).counts();
+    let values = counts.keys().copied().sorted().collect_vec();
+    let Some(&max) = values.last() else { return Vec::new() };
+
+    let mut pairs = Vec::new();
+    for (i, &a) in values.iter().enumerate() {
+        let count = counts[&a];
+        if count > 1 {
+            pairs.push((a, a, count * (count - 1) / 2));
+        }
+
+        // Look for multiples of `a` either by stepping through them or by testing each larger
+        // value, whichever is fewer, rather than trying every pair.
+        let larger = &values[i + 1..];
+        let multiples = if max / a < larger.len() {
+            (2..=max / a).map(|k| k * a).filter(|m| counts.contains_key(m)).collect_vec()
+        }
+        else {
+            larger.iter().copied().filter(|b| b % a == 0).collect_vec()
+        };
+
+        pairs.extend(multiples.into_iter().map(|b| (a, b, count * counts[&b])));
+    }
+
+    pairs
+}
+
+/// The only pair `(a, b)` in `row` where `a` evenly divides `b`, or an error if there are none
+/// or several.
+fn divisible_pair(row: &[usize]) -> Result<(usize, usize), String> {
+    let pairs = divisible_pairs(row);
+
+    match pairs.as_slice() {
+        [(a, b, 1)] => Ok((*a, *b)),
+        [] => Err("no evenly divisible pair".to_string()),
+        _ => {
+            let pairs = pairs.iter()
+                .map(|&(a, b, count)| if count == 1 { format!("{b}/{a}") } else { format!("{b}/{a} x{count}") })
+                .join(", ");
+            Err(format!("several evenly divisible pairs ({pairs})"))
+        }
+    }
+}
+
+/// A way of reducing a row to a number; a spreadsheet's checksum is the sum over its rows.
+trait RowChecksum {
+    fn row(&self, row: &[usize]) -> Result<usize, String>;
+}
+
+/// The difference between the largest and smallest values, as in part 1.
+struct MinMax;
+
+/// The sum of the quotients of every evenly divisible pair, as in part 2, where each row of the
+/// puzzle has only the one; the report insists on that.
+struct DivisibleQuotient;
+
+/// The sum of the greatest common divisors of every pair of values.
+struct GcdPairs;
+
+/// The largest quotient of any evenly divisible pair.
+struct MaxDivisorQuotient;
+
+/// The total distance of every value from the median, taking the lower of the two middle
+/// values when there are an even number.
+struct MedianSpread;
 
-    high - low
+impl RowChecksum for MinMax {
+    fn row(&self, row: &[usize]) -> Result<usize, String> {
+        let (low, high) = minmax(row);
+        Ok(high - low)
+    }
+}
+
+impl RowChecksum for DivisibleQuotient {
+    fn row(&self, row: &[usize]) -> Result<usize, String> {
+        Ok(divisible_pairs(row).iter().map(|&(a, b, count)| count * (b / a)).sum())
+    }
+}
+
+impl RowChecksum for GcdPairs {
+    fn row(&self, row: &[usize]) -> Result<usize, String> {
+        Ok(row.iter().tuple_combinations().map(|(&a, &b)| gcd(a, b)).sum())
+    }
+}
+
+impl RowChecksum for MaxDivisorQuotient {
+    fn row(&self, row: &[usize]) -> Result<usize, String> {
+        divisible_pairs(row).iter()
+            .map(|&(a, b, _)| b / a)
+            .max()
+            .ok_or_else(|| "no evenly divisible pair".to_string())
+    }
 }
 
-fn part1(input: &str) -> usize {
-    let (_, v) = parse_input(input).unwrap();
-    v.into_iter()
-        .map(|v| minmax(&v))
+impl RowChecksum for MedianSpread {
+    fn row(&self, row: &[usize]) -> Result<usize, String> {
+        let mut sorted = row.to_vec();
+        let (_, &mut median, _) = sorted.select_nth_unstable((row.len() - 1) / 2);
+        Ok(row.iter().map(|&v| v.abs_diff(median)).sum())
+    }
+}
+
+/// Every row checksum by the name it is chosen with from the command line.
+const CHECKSUMS: [(&str, &dyn RowChecksum); 5] = [
+    ("minmax", &MinMax),
+    ("quotient", &DivisibleQuotient),
+    ("gcd-pairs", &GcdPairs),
+    ("max-quotient", &MaxDivisorQuotient),
+    ("median-spread", &MedianSpread)
+];
+
+/// Each checksum of the whole spreadsheet, for `run NAME` and `compare`.
+fn checksum_variants() -> Vec<(&'static str, VariantFn)> {
+    CHECKSUMS.iter()
+        .map(|&(name, row)| {
+            let solver: VariantFn = Box::new(move |input| Ok(checksum(&parse_input(input)?, row)?.to_string()));
+            (name, solver)
+        })
+        .collect()
+}
+
+fn checksum(rows: &[Vec<usize>], checksum: &dyn RowChecksum) -> Result<usize, String> {
+    rows.iter().enumerate()
+        .map(|(i, row)| checksum.row(row).map_err(|e| format!("row {}: {e}", i + 1)))
         .sum()
 }
 
-fn part2(input: &str) -> usize {
-    let (_, mut v) = parse_input(input).unwrap();
+/// Everything both checksums look at in one row.
+#[derive(Debug, Eq, PartialEq)]
+struct RowReport {
+    min: usize,
+    max: usize,
+    pair: (usize, usize)
+}
+
+impl RowReport {
+    fn difference(&self) -> usize {
+        self.max - self.min
+    }
+
+    fn quotient(&self) -> usize {
+        self.pair.1 / self.pair.0
+    }
+}
+
+fn report(input: &str) -> Result<Vec<RowReport>, String> {
+    let rows = parse_input(input)?;
+
+    rows.iter().enumerate()
+        .map(|(i, row)| {
+            let (min, max) = minmax(row);
+            let pair = divisible_pair(row).map_err(|e| format!("row {}: {e}", i + 1))?;
+            Ok(RowReport { min, max, pair })
+        })
+        .collect()
+}
+
+fn part1(input: &str) -> Result<usize, String> {
+    let rows = parse_input(input)?;
+    checksum(&rows, &MinMax)
+}
+
+fn part2(input: &str) -> Result<usize, String> {
+    let rows = parse_input(input)?;
+    checksum(&rows, &DivisibleQuotient)
+}
+
+/// Part 2 by trying every pair in each row.
+fn part2_combinations(input: &str) -> Result<usize, String> {
+    let rows = parse_input(input)?;
 
-    v.into_iter()
-        .map(|mut v| { v.sort(); v })
-        .flat_map(|v| v.into_iter().combinations(2).map(|pair| (pair[0], pair[1])))
-        .filter(|&(a, b)| b % a == 0)
+    Ok(rows.iter()
+        .flat_map(|row| row.iter().copied().sorted().tuple_combinations())
+        .filter(|&(a, b)| a != 0 && b % a == 0)
         .map(|(a, b)| b / a)
-        .sum()
+        .sum())
+}
+
+/// The checksum named in `args`, or every checksum, of the spreadsheet read from `reader` a
+/// line at a time, skipping blank lines; errors give the line number.
+fn stream_checksums(reader: &mut dyn Read, args: &[&str]) -> Result<Vec<(&'static str, String)>, String> {
+    let checksums = match args {
+        [] => CHECKSUMS.to_vec(),
+        [name] => {
+            let checksum = CHECKSUMS.iter().find(|(n, _)| n == name).ok_or_else(|| {
+                let names = CHECKSUMS.iter().map(|(n, _)| n).join(", ");
+                format!("unknown checksum {name:?}, expected one of {names}")
+            })?;
+            vec![*checksum]
+        },
+        _ => return Err("usage: stream [CHECKSUM]".to_string())
+    };
+
+    let mut totals: Vec<Result<usize, String>> = vec![Ok(0); checksums.len()];
+    for (i, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (_, row) = all_consuming(fields(unsigned::<usize>))(line.trim())
+            .map_err(|_| format!("line {}: invalid row {line:?}", i + 1))?;
+
+        for ((_, checksum), total) in checksums.iter().zip(totals.iter_mut()) {
+            if let Ok(sum) = total {
+                *total = checksum.row(&row).map(|n| *sum + n).map_err(|e| format!("line {}: {e}", i + 1));
+            }
+        }
+    }
+
+    Ok(checksums.iter().zip(totals)
+        .map(|((name, _), total)| (*name, total.map_or_else(|e| format!("error: {e}"), |n| n.to_string())))
+        .collect())
+}
+
+fn write_report(input: &str, dir: &Path, _args: &[&str]) -> io::Result<()> {
+    let rows = report(input).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    save(dir.join("day02-report.txt"), |out| {
+        writeln!(out, "row\tmin\tmax\tdifference\tdividend\tdivisor\tquotient")?;
+        for (i, row) in rows.iter().enumerate() {
+            let (divisor, dividend) = row.pair;
+            writeln!(out, "{}\t{}\t{}\t{}\t{dividend}\t{divisor}\t{}",
+                     i + 1, row.min, row.max, row.difference(), row.quotient())?;
+        }
+        Ok(())
+    })
 }
 
-build_main!("day02.txt", "Part 1" => part1, "Part 2" => part2);
+build_main_res!("day02.txt",
+    "Part 1" => { "minmax" => part1 },
+    "Part 2" => { "search" => part2, "combinations" => part2_combinations };
+    variants "Checksum" => checksum_variants();
+    render "report" => write_report;
+    stream stream_checksums);
 
 #[cfg(test)]
 mod tests {
@@ -59,6 +284,62 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(TEST_INPUT), 18);
+        assert_eq!(part1(TEST_INPUT), Ok(18));
+        assert_eq!(part1("5\t1\t9\t5\n7,5, 3\n2 ,4\t6  8\n"), Ok(18));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(part1("5 1\r\n7 5 3\r\n"), Ok(8));
+        assert_eq!(part1("5 1\n7 5 x\n"), Err("invalid spreadsheet row 2: \"7 5 x\"".to_string()));
+        assert_eq!(part2("5 1\n\n2 4"), Err("invalid spreadsheet row 2: \"\"".to_string()));
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2("5 9 2 8\n9 4 7 3\n3 8 6 5"), Ok(9));
+        assert_eq!(report("5,9,2,8").unwrap(), vec![RowReport { min: 2, max: 9, pair: (2, 8) }]);
+
+        assert_eq!(part2(TEST_INPUT), Ok(31));
+        assert_eq!(part2_combinations(TEST_INPUT), Ok(31));
+        assert_eq!(part2_combinations("5 9 2 8\n9 4 7 3\n3 8 6 5"), Ok(9));
+
+        assert_eq!(report(TEST_INPUT).unwrap_err(), "row 1: several evenly divisible pairs (5/1 x2, 9/1, 5/5)");
+        assert_eq!(report("3 8 6 5\n7 5 3").unwrap_err(), "row 2: no evenly divisible pair");
+    }
+
+    #[test]
+    fn test_divisible_pairs() {
+        assert_eq!(divisible_pairs(&[6, 0, 3, 6, 7, 12]), vec![(3, 6, 2), (3, 12, 1), (6, 6, 1), (6, 12, 2)]);
+
+        // Both ways of looking for multiples should find the same pairs as trying every pair.
+        let row = (0..200).map(|i| (i * 7919) % 1000 + 1).collect_vec();
+        let expected = row.iter().copied()
+            .sorted()
+            .tuple_combinations()
+            .filter(|&(a, b)| b % a == 0)
+            .count();
+        assert_eq!(divisible_pairs(&row).iter().map(|&(_, _, count)| count).sum::<usize>(), expected);
+    }
+
+    #[test]
+    fn test_checksums() {
+        let rows = vec![vec![5, 9, 2, 8], vec![9, 4, 7, 3], vec![3, 8, 6, 5]];
+        let results = CHECKSUMS.iter().map(|&(_, c)| checksum(&rows, c)).collect_vec();
+        assert_eq!(results, vec![Ok(18), Ok(9), Ok(24), Ok(9), Ok(25)]);
+
+        let variants = checksum_variants();
+        assert_eq!(variants[2].0, "gcd-pairs");
+        assert_eq!(variants[2].1("5 9 2 8\n9 4 7 3\n3 8 6 5"), Ok("24".to_string()));
+        assert_eq!(variants[3].1("5 3"), Err("row 1: no evenly divisible pair".to_string()));
+
+        let results = stream_checksums(&mut "4, 2\n\n5\t3\n".as_bytes(), &[]).unwrap();
+        assert_eq!(results[0], ("minmax", "4".to_string()));
+        assert_eq!(results[1], ("quotient", "2".to_string()));
+        assert_eq!(results[3], ("max-quotient", "error: line 3: no evenly divisible pair".to_string()));
+
+        let results = stream_checksums(&mut "5 9 2 8".as_bytes(), &["median-spread"]).unwrap();
+        assert_eq!(results, vec![("median-spread", "10".to_string())]);
+        assert!(stream_checksums(&mut "".as_bytes(), &["mean"]).unwrap_err().contains("unknown checksum"));
     }
-}
\ No newline at end of file
+}
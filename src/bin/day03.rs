@@ -1,101 +1,102 @@
-use std::collections::HashMap;
-use std::ops::{Add, AddAssign};
-use adventofcode2017::build_main;
-
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-struct Pos(isize, isize);
-
-impl Pos {
-    fn rotate(&self) -> Pos {
-        Pos(-self.1, self.0)
-    }
-
-    fn neighbors(&self) -> [Pos; 8] {
-        [
-            *self + Pos(1, 0),
-            *self + Pos(1, 1),
-            *self + Pos(0, 1),
-            *self + Pos(-1, 1),
-            *self + Pos(-1, 0),
-            *self + Pos(-1, -1),
-            *self + Pos(0, -1),
-            *self + Pos(1, -1)
-        ]
-
-    }
+use std::io::{self, Write};
+use std::path::Path;
+use adventofcode2017::build_main_res;
+use adventofcode2017::render::save;
+use adventofcode2017::spiral::{square_position, RingIter, StressTest};
+
+fn parse_input(input: &str) -> Result<usize, String> {
+    let input = input.trim();
+    input.parse::<usize>().map_err(|_| format!("invalid number {input:?}"))
 }
 
-impl Add for Pos {
-    type Output = Pos;
-    fn add(self, rhs: Self) -> Self::Output {
-        Pos(self.0 + rhs.0, self.1 + rhs.1)
+/// The input as a square number, which count from 1.
+fn parse_square(input: &str) -> Result<usize, String> {
+    match parse_input(input)? {
+        0 => Err("squares are numbered from 1".to_string()),
+        n => Ok(n)
     }
 }
 
-impl AddAssign for Pos {
-    fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
-        self.1 += rhs.1;
-    }
+fn part1(input: &str) -> Result<isize, String> {
+    let n = parse_square(input)?;
+    Ok(square_position(n).manhattan())
 }
 
-struct RingIter {
-    i: usize,
-    pos: Pos,
-    delta: Pos
+/// Part 1 by walking the spiral out to square `n`.
+fn part1_walk(input: &str) -> Result<isize, String> {
+    let n = parse_square(input)?;
+    Ok(RingIter::new().nth(n - 1).unwrap().1.manhattan())
 }
 
-impl RingIter {
-    fn new() -> RingIter {
-        RingIter { i: 1, pos: Pos(0, 0), delta: Pos(0, -1) }
-    }
-}
+/// The first value written larger than the input.
+fn part2(input: &str) -> Result<usize, String> {
+    let n = parse_input(input)?;
 
-impl Iterator for RingIter {
-    type Item = (usize, Pos);
-    fn next(&mut self) -> Option<Self::Item> {
-        let result = Some((self.i, self.pos));
-
-        let Pos(x, y) = self.pos;
-        if x == y || (x < 0 && y == -x) || (x > 0 && x == 1 - y ){
-            self.delta = self.delta.rotate();
+    for result in StressTest::new() {
+        let (_, _, value) = result?;
+        if value > n {
+            return Ok(value);
         }
-
-        self.pos += self.delta;
-        self.i += 1;
-
-        result
     }
-}
 
-fn part1(input: &str) -> isize {
-    let n = input.parse::<usize>().unwrap();
-    let Pos(x, y) = RingIter::new().nth(n - 1).unwrap().1;
-    x.abs() + y.abs()
+    unreachable!()
 }
 
-fn part2(input: &str) -> usize {
-    let n = input.parse::<usize>().unwrap();
-    let mut results = HashMap::new();
-
-    for (i, pos) in RingIter::new() {
-        if i == 1 {
-            results.insert(pos, 1usize);
-            continue;
+/// The stress test values up to the first one larger than the input, laid out as in the puzzle:
+/// right-aligned columns with the top row first, and squares not yet reached left blank.
+fn write_stress_test(input: &str, dir: &Path, _args: &[&str]) -> io::Result<()> {
+    let n = parse_input(input).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut squares = Vec::new();
+    for result in StressTest::new() {
+        let (_, pos, value) = result.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        squares.push((pos, value));
+        if value > n {
+            break;
         }
+    }
 
-        let result = pos.neighbors().into_iter()
-            .filter_map(|p| results.get(&p).map(|&n| n))
-            .sum::<usize>();
+    let k = squares.iter().map(|(pos, _)| pos.chebyshev()).max().unwrap();
+    let side = (2 * k + 1) as usize;
+    let mut cells = vec![vec![String::new(); side]; side];
+    for (pos, value) in squares {
+        cells[(k - pos.1) as usize][(pos.0 + k) as usize] = value.to_string();
+    }
 
-        if result >= n {
-            return result;
+    let width = cells.iter().flatten().map(|c| c.len()).max().unwrap();
+    save(dir.join("day03-stress.txt"), |out| {
+        for row in cells {
+            let line = row.iter().map(|c| format!("{c:>width$}")).collect::<Vec<_>>().join("  ");
+            writeln!(out, "{}", line.trim_end())?;
         }
+        Ok(())
+    })
+}
 
-        results.insert(pos, result);
+build_main_res!("day03.txt",
+    "Part 1" => { "closed-form" => part1, "walk" => part1_walk },
+    "Part 2" => { "default" => part2 };
+    render "stress" => write_stress_test);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1("1"), Ok(0));
+        assert_eq!(part1("12"), Ok(3));
+        assert_eq!(part1("23"), Ok(2));
+        assert_eq!(part1("1024\n"), Ok(31));
+        assert_eq!(part1_walk("1024\n"), Ok(31));
+        assert!(part1_walk("0").is_err());
+        assert_eq!(part1("twelve"), Err("invalid number \"twelve\"".to_string()));
     }
 
-    unreachable!()
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2("800"), Ok(806));
+        assert_eq!(part2("147"), Ok(304));
+        assert!(part2(&usize::MAX.to_string()).unwrap_err().contains("overflows"));
+    }
 }
-
-build_main!("day03.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
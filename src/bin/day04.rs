@@ -1,48 +1,326 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::hash::Hash;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
 use itertools::Itertools;
 use adventofcode2017::build_main;
+use adventofcode2017::render::save;
 
-fn no_repeats(passphrase: &[&str]) -> bool {
-    let mut seen = HashSet::new();
-    for &word in passphrase {
-        if !seen.insert(word) {
-            return false;
+/// A kind of character a policy may allow in its words.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum CharClass { Lower, Upper, Alpha, Digit, Punct }
+
+impl CharClass {
+    fn contains(self, c: char) -> bool {
+        match self {
+            CharClass::Lower => c.is_lowercase(),
+            CharClass::Upper => c.is_uppercase(),
+            CharClass::Alpha => c.is_alphabetic(),
+            CharClass::Digit => c.is_numeric(),
+            CharClass::Punct => c.is_ascii_punctuation()
         }
     }
+}
+
+impl FromStr for CharClass {
+    type Err = String;
 
-    true
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lower" => Ok(CharClass::Lower),
+            "upper" => Ok(CharClass::Upper),
+            "alpha" => Ok(CharClass::Alpha),
+            "digit" => Ok(CharClass::Digit),
+            "punct" => Ok(CharClass::Punct),
+            _ => Err(format!("invalid character class {s:?}"))
+        }
+    }
 }
 
-fn no_anagrams(passphrase: &[&str]) -> bool {
-    let mut seen = HashSet::new();
+/// The first rule a passphrase breaks, naming the words responsible.
+#[derive(Debug, Eq, PartialEq)]
+enum Violation<'a> {
+    TooFewWords(usize),
+    TooManyWords(usize),
+    Character(&'a str, char),
+    Duplicate(&'a str, &'a str),
+    Anagram(&'a str, &'a str)
+}
 
-    for &word in passphrase {
-        let mut sig = [0; 26];
-        for c in word.chars() {
-            let ord = c as usize - 'a' as usize;
-            sig[ord] += 1;
+impl Violation<'_> {
+    fn rule(&self) -> &'static str {
+        match self {
+            Violation::TooFewWords(_) => "min-words",
+            Violation::TooManyWords(_) => "max-words",
+            Violation::Character(..) => "chars",
+            Violation::Duplicate(..) => "no-duplicates",
+            Violation::Anagram(..) => "no-anagrams"
         }
+    }
+}
 
-        if !seen.insert(sig) {
-            return false;
+impl Display for Violation<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::TooFewWords(n) => write!(f, "too few words ({n})"),
+            Violation::TooManyWords(n) => write!(f, "too many words ({n})"),
+            Violation::Character(word, c) => write!(f, "{c:?} in {word:?} is not allowed"),
+            Violation::Duplicate(a, b) => write!(f, "{a:?} repeats {b:?}"),
+            Violation::Anagram(a, b) => write!(f, "{a:?} is an anagram of {b:?}")
         }
     }
+}
 
-    true
+/// The rules a passphrase must follow. Everything is allowed until a builder method adds a
+/// rule; `fold_case` makes the duplicate and anagram rules ignore case.
+#[derive(Clone, Debug, Default)]
+struct Policy {
+    no_duplicates: bool,
+    no_anagrams: bool,
+    fold_case: bool,
+    min_words: usize,
+    max_words: Option<usize>,
+    chars: Vec<CharClass>
 }
 
-fn part1(input: &str) -> usize {
+impl Policy {
+    fn new() -> Policy {
+        Policy::default()
+    }
+
+    fn no_duplicates(mut self) -> Policy {
+        self.no_duplicates = true;
+        self
+    }
+
+    fn no_anagrams(mut self) -> Policy {
+        self.no_anagrams = true;
+        self
+    }
+
+    fn fold_case(mut self) -> Policy {
+        self.fold_case = true;
+        self
+    }
+
+    fn min_words(mut self, n: usize) -> Policy {
+        self.min_words = n;
+        self
+    }
+
+    fn max_words(mut self, n: usize) -> Policy {
+        self.max_words = Some(n);
+        self
+    }
+
+    /// Allows characters of `class`; once any class is allowed, characters in none are not.
+    fn allow(mut self, class: CharClass) -> Policy {
+        self.chars.push(class);
+        self
+    }
+
+    fn fold(&self, word: &str) -> String {
+        if self.fold_case { fold_case(word) } else { word.to_string() }
+    }
+
+    fn check<'a>(&self, passphrase: &'a str) -> Result<(), Violation<'a>> {
+        let words = passphrase.split_whitespace().collect_vec();
+
+        if words.len() < self.min_words {
+            return Err(Violation::TooFewWords(words.len()));
+        }
+        if self.max_words.is_some_and(|max| words.len() > max) {
+            return Err(Violation::TooManyWords(words.len()));
+        }
+
+        if !self.chars.is_empty() {
+            for &word in words.iter() {
+                if let Some(c) = word.chars().find(|&c| !self.chars.iter().any(|class| class.contains(c))) {
+                    return Err(Violation::Character(word, c));
+                }
+            }
+        }
+
+        if self.no_duplicates {
+            first_conflict(&words, |word| self.fold(word)).map_or(Ok(()), |(a, b)| Err(Violation::Duplicate(a, b)))?;
+        }
+        if self.no_anagrams {
+            first_conflict(&words, |word| signature(&self.fold(word))).map_or(Ok(()), |(a, b)| Err(Violation::Anagram(a, b)))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A policy written as comma-separated rules, as named by `Violation::rule`, for example
+/// `no-anagrams,max-words=10,chars=lower+digit,fold-case`.
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |rule: &str, n: &str| n.parse::<usize>().map_err(|_| format!("invalid {rule} {n:?}"));
+
+        s.split(',').try_fold(Policy::new(), |policy, rule| {
+            match rule.split_once('=') {
+                None if rule == "no-duplicates" => Ok(policy.no_duplicates()),
+                None if rule == "no-anagrams" => Ok(policy.no_anagrams()),
+                None if rule == "fold-case" => Ok(policy.fold_case()),
+                Some(("min-words", n)) => Ok(policy.min_words(number("min-words", n)?)),
+                Some(("max-words", n)) => Ok(policy.max_words(number("max-words", n)?)),
+                Some(("chars", classes)) => classes.split('+')
+                    .try_fold(policy, |policy, class| Ok(policy.allow(class.parse()?))),
+                _ => Err(format!("invalid policy rule {rule:?}"))
+            }
+        })
+    }
+}
+
+/// The first later word whose `key` matches an earlier word's, paired with that earlier word.
+fn first_conflict<'a, K: Eq + Hash>(words: &[&'a str], key: impl Fn(&str) -> K) -> Option<(&'a str, &'a str)> {
+    let mut seen = HashMap::new();
+
+    words.iter().find_map(|&word| seen.insert(key(word), word).map(|earlier| (word, earlier)))
+}
+
+/// `word` lower-cased a character at a time, so that no character depends on its neighbours,
+/// with final sigma folded into sigma as Unicode case folding does.
+fn fold_case(word: &str) -> String {
+    word.chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| if c == 'ς' { 'σ' } else { c })
+        .collect()
+}
+
+/// The characters of `word` in order, shared by exactly its anagrams. Works on any Unicode,
+/// though a character written precomposed and as a base with combining marks count as different.
+fn signature(word: &str) -> Vec<char> {
+    word.chars().sorted_unstable().collect()
+}
+
+fn count_valid(input: &str, policy: &Policy) -> usize {
     input.lines()
-        .map(|line| line.split(' ').collect_vec())
-        .filter(|passphrase| no_repeats(passphrase))
+        .filter(|passphrase| policy.check(passphrase).is_ok())
         .count()
 }
 
+fn part1(input: &str) -> usize {
+    count_valid(input, &Policy::new().no_duplicates())
+}
+
 fn part2(input: &str) -> usize {
-    input.lines()
-        .map(|line| line.split(' ').collect_vec())
-        .filter(|passphrase| no_anagrams(passphrase))
-        .count()
+    count_valid(input, &Policy::new().no_anagrams())
+}
+
+/// Every passphrase rejected by the policy given as the only argument, or by each part's
+/// policy without one, with the rule and the words that broke it.
+fn write_report(input: &str, dir: &Path, args: &[&str]) -> io::Result<()> {
+    let policies = match args {
+        [] => vec![("Part 1", Policy::new().no_duplicates()), ("Part 2", Policy::new().no_anagrams())],
+        [policy] => vec![(*policy, policy.parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?)],
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "usage: render [DIR [POLICY]]"))
+    };
+
+    save(dir.join("day04-report.txt"), |out| report(out, input, &policies))
 }
 
-build_main!("day04.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
+fn report(out: &mut dyn Write, input: &str, policies: &[(&str, Policy)]) -> io::Result<()> {
+    writeln!(out, "policy\tline\trule\tviolation\tpassphrase")?;
+    for (name, policy) in policies.iter() {
+        for (i, passphrase) in input.lines().enumerate() {
+            if let Err(violation) = policy.check(passphrase) {
+                writeln!(out, "{name}\t{}\t{}\t{violation}\t{passphrase}", i + 1, violation.rule())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks each passphrase read from `reader` against the policy given as the only argument,
+/// counting the valid ones and the invalid ones by rule.
+fn stream_policy(reader: &mut dyn Read, args: &[&str]) -> Result<Vec<(&'static str, String)>, String> {
+    let policy = match args {
+        [policy] => policy.parse::<Policy>()?,
+        _ => return Err("usage: stream POLICY".to_string())
+    };
+
+    let mut valid = 0;
+    let mut rejected = HashMap::new();
+    for line in BufReader::new(reader).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        match policy.check(&line) {
+            Ok(()) => valid += 1,
+            Err(violation) => *rejected.entry(violation.rule()).or_insert(0) += 1
+        }
+    }
+
+    let mut results = vec![("valid", valid.to_string())];
+    results.extend(rejected.into_iter().sorted().map(|(rule, n)| (rule, n.to_string())));
+    Ok(results)
+}
+
+build_main!("day04.txt", "Part 1" => part1, "Part 2" => part2; render "report" => write_report; stream stream_policy);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parts() {
+        assert_eq!(part1("aa bb cc dd ee\naa bb cc dd aa\naa bb cc dd aaa"), 2);
+        assert_eq!(part2("abcde fghij\nabcde xyz ecdab\na ab abc abd abf abj\niiii oiii ooii oooi oooo\noiii ioii iioi iiio"), 3);
+    }
+
+    #[test]
+    fn test_policy() {
+        let policy = "no-duplicates,min-words=2,max-words=4,chars=lower+digit,fold-case".parse::<Policy>().unwrap();
+        assert_eq!(policy.check("ab cd"), Ok(()));
+        assert_eq!(policy.check("ab"), Err(Violation::TooFewWords(1)));
+        assert_eq!(policy.check("a b c d e"), Err(Violation::TooManyWords(5)));
+        assert_eq!(policy.check("ab c-d"), Err(Violation::Character("c-d", '-')));
+        assert_eq!(policy.check("ab cd ab"), Err(Violation::Duplicate("ab", "ab")));
+
+        let policy = Policy::new().no_anagrams();
+        assert_eq!(policy.check("abc cab"), Err(Violation::Anagram("cab", "abc")));
+        assert_eq!(policy.check("abc cab").unwrap_err().to_string(), "\"cab\" is an anagram of \"abc\"");
+
+        assert_eq!(Policy::new().fold_case().no_duplicates().check("Hello HELLO"), Err(Violation::Duplicate("HELLO", "Hello")));
+
+        assert!("no-repeats".parse::<Policy>().unwrap_err().contains("invalid policy rule"));
+        assert!("chars=lower+emoji".parse::<Policy>().unwrap_err().contains("invalid character class"));
+
+        let results = stream_policy(&mut "aa bb\naa bb aa\naa\n".as_bytes(), &["no-duplicates,min-words=2"]).unwrap();
+        assert_eq!(results, vec![
+            ("valid", "1".to_string()),
+            ("min-words", "1".to_string()),
+            ("no-duplicates", "1".to_string())
+        ]);
+    }
+
+    #[test]
+    fn test_report() {
+        let policies = [("max-words=2", "max-words=2".parse::<Policy>().unwrap()), ("Part 2", Policy::new().no_anagrams())];
+        let mut out = Vec::new();
+        report(&mut out, "ab ba\nab cd ab", &policies).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "policy\tline\trule\tviolation\tpassphrase\n\
+            max-words=2\t2\tmax-words\ttoo many words (3)\tab cd ab\n\
+            Part 2\t1\tno-anagrams\t\"ba\" is an anagram of \"ab\"\tab ba\n\
+            Part 2\t2\tno-anagrams\t\"ab\" is an anagram of \"ab\"\tab cd ab\n");
+    }
+
+    #[test]
+    fn test_unicode_anagrams() {
+        let policy = Policy::new().no_anagrams();
+        assert_eq!(policy.check("листок столик"), Err(Violation::Anagram("столик", "листок")));
+        assert_eq!(policy.check("日本 本日 東京"), Err(Violation::Anagram("本日", "日本")));
+        assert_eq!(policy.check("café éfac 🦀x x🦀"), Err(Violation::Anagram("éfac", "café")));
+        assert_eq!(policy.check("a1 1b Zz zZ"), Err(Violation::Anagram("zZ", "Zz")));
+        assert_eq!(policy.check("Listen Silent Ωμέγα ωμέγα"), Ok(()));
+
+        let policy = Policy::new().no_anagrams().fold_case();
+        assert_eq!(policy.check("Listen Silent"), Err(Violation::Anagram("Silent", "Listen")));
+        assert_eq!(policy.check("abc ΣΑ ας"), Err(Violation::Anagram("ας", "ΣΑ")));
+        assert_eq!(policy.check("Ωμέγα γαμΩΈ"), Err(Violation::Anagram("γαμΩΈ", "Ωμέγα")));
+    }
+}
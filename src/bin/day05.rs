@@ -1,49 +1,74 @@
 use itertools::Itertools;
 use adventofcode2017::build_main;
+use adventofcode2017::simulation::Simulation;
 
-fn part1(input: &str) -> usize {
-    let mut jumps = input.lines()
-        .map(|line| line.parse::<isize>().unwrap())
-        .collect_vec();
-
-    let mut count = 0;
-    let mut i = 0isize;
-    let mut j = i as usize;
-
-    while i >= 0 && (i as usize) < jumps.len() {
-        j = i as usize;
-        let cur = jumps[j];
-        jumps[j] += 1;
-        i += cur;
-        count += 1;
-    }
+#[derive(Clone)]
+struct Jumps {
+    offsets: Vec<isize>,
+    cur_ptr: isize,
+    strange: bool,
+    steps: usize
+}
 
-    count
+impl Jumps {
+    /// With `strange` set, offsets of three or more shrink after each jump instead of growing.
+    fn new(input: &str, strange: bool) -> Jumps {
+        let offsets = input.lines()
+            .map(|line| line.parse::<isize>().unwrap())
+            .collect_vec();
+
+        Jumps { offsets, cur_ptr: 0, strange, steps: 0 }
+    }
 }
 
-fn part2(input: &str) -> usize {
-    let mut jumps = input.lines()
-        .map(|line| line.parse::<isize>().unwrap())
-        .collect_vec();
+impl Simulation for Jumps {
+    type Output = isize;
+    type Snapshot = Jumps;
 
-    let mut count = 0;
-    let mut i = 0isize;
+    /// Takes one jump, returning where it landed, or `None` once outside the list.
+    fn step(&mut self) -> Option<isize> {
+        if self.cur_ptr < 0 || self.cur_ptr as usize >= self.offsets.len() {
+            return None;
+        }
 
-    while i >= 0 && (i as usize) < jumps.len() {
-        let j = i as usize;
-        let cur = jumps[j];
+        let j = self.cur_ptr as usize;
+        let cur = self.offsets[j];
 
-        if jumps[j] >= 3 {
-            jumps[j] -= 1;
+        if self.strange && cur >= 3 {
+            self.offsets[j] -= 1;
         }
         else {
-            jumps[j] += 1;
+            self.offsets[j] += 1;
         }
-        i += cur;
-        count += 1;
+        self.cur_ptr += cur;
+        self.steps += 1;
+
+        Some(self.cur_ptr)
+    }
+
+    fn steps(&self) -> usize {
+        self.steps
     }
 
-    count
+    fn snapshot(&self) -> Jumps {
+        self.clone()
+    }
+
+    fn restore(&mut self, snapshot: Jumps) {
+        *self = snapshot;
+    }
+}
+
+fn part1(input: &str) -> usize {
+    let mut jumps = Jumps::new(input, false);
+    jumps.run();
+    jumps.steps()
+}
+
+fn part2(input: &str) -> usize {
+    let mut jumps = Jumps::new(input, true);
+    jumps.run();
+    jumps.steps()
 }
 
-build_main!("day05.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
+build_main!("day05.txt", "Part 1" => part1, "Part 2" => part2);
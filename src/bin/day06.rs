@@ -1,54 +1,107 @@
-use std::collections::HashMap;
 use itertools::Itertools;
 use adventofcode2017::build_main;
+use adventofcode2017::cycle::{brent, hashed};
+use adventofcode2017::simulation::Simulation;
 
-fn find_loop(mut cur: Vec<usize>) -> (usize, usize) {
-    let mut seen = HashMap::new();
-    let mut loop_count = 0;
-
-    seen.insert(cur.clone(), loop_count);
-
-    loop {
-        let (max_pos, max_val) = cur.iter().enumerate()
-            .fold((0, 0), |(max_pos, max_val), (i, &next)| {
-                if next > max_val {
-                    (i, next)
-                }
-                else {
-                    (max_pos, max_val)
-                }
-            });
-
-        cur[max_pos] = 0;
-        (0..max_val).for_each(|delta| {
-            let j = (max_pos + 1 + delta) % cur.len();
-            cur[j] += 1;
+fn redistribute(banks: &[usize]) -> Vec<usize> {
+    let mut cur = banks.to_vec();
+    let (max_pos, max_val) = cur.iter().enumerate()
+        .fold((0, 0), |(max_pos, max_val), (i, &next)| {
+            if next > max_val {
+                (i, next)
+            }
+            else {
+                (max_pos, max_val)
+            }
         });
 
-        loop_count += 1;
+    cur[max_pos] = 0;
+    (0..max_val).for_each(|delta| {
+        let j = (max_pos + 1 + delta) % cur.len();
+        cur[j] += 1;
+    });
 
-        let orig = seen.insert(cur.clone(), loop_count);
-        match orig {
-            None => continue,
-            Some(orig) => return (seen.len(), loop_count - orig)
-        }
+    cur
+}
+
+/// The memory banks as a simulation, counting the redistributions so far. The cycle finders
+/// work on the blocks alone, since the count never repeats.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Banks {
+    blocks: Vec<usize>,
+    steps: usize
+}
+
+impl Banks {
+    fn new(input: &str) -> Banks {
+        let blocks = input.split_whitespace()
+            .map(|w| w.parse::<usize>().unwrap())
+            .collect_vec();
+
+        Banks { blocks, steps: 0 }
+    }
+}
+
+impl Simulation for Banks {
+    type Output = ();
+    type Snapshot = Banks;
+
+    fn step(&mut self) -> Option<()> {
+        self.blocks = redistribute(&self.blocks);
+        self.steps += 1;
+        Some(())
+    }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+
+    fn snapshot(&self) -> Banks {
+        self.clone()
+    }
+
+    fn restore(&mut self, snapshot: Banks) {
+        *self = snapshot;
     }
 }
 
 fn part1(input: &str) -> usize {
-    let cur = input.split('\t')
-        .map(|w| w.parse::<usize>().unwrap())
-        .collect_vec();
+    let cycle = hashed(&Banks::new(input).blocks, |blocks| redistribute(blocks));
+    cycle.start + cycle.length
+}
 
-    find_loop(cur).0
+fn part1_brent(input: &str) -> usize {
+    let cycle = brent(&Banks::new(input).blocks, |blocks| redistribute(blocks));
+    cycle.start + cycle.length
 }
 
 fn part2(input: &str) -> usize {
-    let cur = input.split('\t')
-        .map(|w| w.parse::<usize>().unwrap())
-        .collect_vec();
+    brent(&Banks::new(input).blocks, |blocks| redistribute(blocks)).length
+}
 
-    find_loop(cur).1
+fn part2_hashed(input: &str) -> usize {
+    hashed(&Banks::new(input).blocks, |blocks| redistribute(blocks)).length
 }
 
-build_main!("day06.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
+build_main!(
+    "day06.txt",
+    "Part 1" => { "hashed" => part1, "brent" => part1_brent },
+    "Part 2" => { "brent" => part2, "hashed" => part2_hashed }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parts() {
+        assert_eq!(part1("0\t2\t7\t0"), 5);
+        assert_eq!(part1_brent("0 2 7 0\n"), 5);
+        assert_eq!(part2("0\t2\t7\t0"), 4);
+        assert_eq!(part2_hashed("0\t2\t7\t0"), 4);
+
+        let mut banks = Banks::new("0 2 7 0");
+        banks.run_for(5);
+        assert_eq!(banks, Banks { blocks: vec![2, 4, 1, 2], steps: 5 });
+    }
+}
@@ -1,8 +1,10 @@
 use adventofcode2017::build_main;
+use adventofcode2017::graph::Graph;
+use adventofcode2017::parse::{lines, unsigned};
 use itertools::Itertools;
 use nom::bytes::complete::tag;
-use nom::character::complete::{alpha1, digit1, newline};
-use nom::combinator::{map, map_res, opt};
+use nom::character::complete::alpha1;
+use nom::combinator::{map, opt};
 use nom::multi::separated_list1;
 use nom::sequence::{delimited, preceded, tuple};
 use nom::IResult;
@@ -15,16 +17,12 @@ struct Program<'a> {
     holding: Vec<&'a str>
 }
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Program>> {
-    fn number(input: &str) -> IResult<&str, usize> {
-        map_res(digit1, |s: &str| s.parse::<usize>())(input)
-    }
-
-    fn program(input: &str) -> IResult<&str, Program> {
+fn parse_input(input: &str) -> IResult<&str, Vec<Program<'_>>> {
+    fn program(input: &str) -> IResult<&str, Program<'_>> {
         map(
             tuple((
                 alpha1,
-                delimited(tag(" ("), number, tag(")")),
+                delimited(tag(" ("), unsigned, tag(")")),
                 opt(
                     preceded(
                         tag(" -> "),
@@ -39,36 +37,33 @@ fn parse_input(input: &str) -> IResult<&str, Vec<Program>> {
         )(input)
     }
 
-    separated_list1(newline, program)(input)
+    lines(program)(input)
 }
 
-fn find_root<'a>(programs: &[Program<'a>]) -> &'a str {
-    let mut pred: HashMap<&str, Option<&str>> = HashMap::new();
-
-    for Program { name, holding, ..} in programs.iter() {
-        pred.entry(*name).or_default();
+fn build_graph<'a>(programs: &[Program<'a>]) -> Graph<&'a str> {
+    let mut graph = Graph::new();
 
-        for &succ in holding.iter() {
-            pred.insert(succ, Some(name));
-        }
+    for Program { name, holding, .. } in programs.iter() {
+        graph.add_node(*name);
+        holding.iter().for_each(|&succ| graph.add_edge(*name, succ));
     }
 
-    pred.into_iter()
-        .filter(|&(_, v)| v.is_none())
-        .nth(0)
-        .unwrap()
-        .0
+    graph
 }
 
 fn part1(input: &str) -> &str {
     let programs = parse_input(input).unwrap().1;
-    find_root(&programs)
+    build_graph(&programs).topological_sort().unwrap()[0]
 }
 
 fn part2(input: &str) -> usize {
     let programs = parse_input(input).unwrap().1;
+    let graph = build_graph(&programs);
 
-    let root = find_root(&programs);
+    // Parents come before their children, so walking the order backwards visits every program
+    // after everything it holds.
+    let order = graph.topological_sort().unwrap();
+    let root = *order[0];
 
     let map: HashMap<&str, Program> = programs.into_iter()
         .map(|p| (p.name, p))
@@ -77,43 +72,29 @@ fn part2(input: &str) -> usize {
     let mut weights = HashMap::new();
     let mut unbalanced_children = HashMap::new();
 
-    let mut stack = vec![root];
-
-    while let Some(&name) = stack.last() {
-        let mut unsat_children = map[&name].holding.iter()
-            .filter(|&child| !weights.contains_key(child))
-            .cloned()
+    for &name in order.into_iter().rev() {
+        let child_weights: Vec<(&str, usize)> = map[&name].holding.iter()
+            .map(|name| (*name, weights[name]))
             .collect_vec();
 
-        if unsat_children.is_empty() {
-            let child_weights: Vec<(&str, usize)> = map[&name].holding.iter()
-                .map(|name| (*name, weights[name]))
-                .collect_vec();
+        let weight = map[&name].weight + child_weights.iter().map(|(_, w)| *w).sum::<usize>();
+        weights.insert(name, weight);
 
-            let weight = map[&name].weight + child_weights.iter().map(|(_, w)| *w).sum::<usize>();
-            weights.insert(name, weight);
+        let unique_weight = child_weights.iter()
+            .map(|(_, w)| *w)
+            .counts()
+            .into_iter()
+            .find(|(_, v)| *v == 1)
+            .map(|(k, _)| k);
 
-            let unique_weight = child_weights.iter()
-                .map(|(_, w)| *w)
-                .counts()
-                .into_iter()
-                .find(|(_, v)| *v == 1)
-                .map(|(k, _)| k);
+        let unbalanced_child = unique_weight.map(|w| {
+            child_weights.iter()
+                .find(|(_, v)| *v == w)
+                .map(|(k, _)| *k)
+                .unwrap()
+        });
 
-            let unbalanced_child = unique_weight.map(|w| {
-                child_weights.iter()
-                    .find(|(_, v)| *v == w)
-                    .map(|(k, _)| *k)
-                    .unwrap()
-            });
-
-            unbalanced_child.iter().for_each(|&k| { unbalanced_children.insert(name, k); });
-
-            stack.pop();
-        }
-        else {
-            stack.append(&mut unsat_children);
-        }
+        unbalanced_child.iter().for_each(|&k| { unbalanced_children.insert(name, k); });
     }
 
     let mut cur = root;
@@ -2,12 +2,12 @@ use std::cmp::max;
 use std::collections::HashMap;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::character::complete::{alpha1, char, digit1, newline, space1};
-use nom::combinator::{map, map_res, opt, recognize, value};
+use nom::character::complete::{alpha1, space1};
+use nom::combinator::{map, value};
 use nom::IResult;
-use nom::multi::separated_list1;
 use nom::sequence::{delimited, preceded, tuple};
 use adventofcode2017::build_main;
+use adventofcode2017::parse::{lines, signed};
 
 #[derive(Copy, Clone)]
 enum Comp { Le, Lt, Eq, Ne, Gt, Ge }
@@ -31,18 +31,11 @@ struct Rule<'a> {
     condition: Condition<'a>
 }
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Rule>> {
-    fn number(input: &str) -> IResult<&str, isize> {
-        map_res(
-            recognize(tuple((opt(char('-')), digit1))),
-            |s: &str| s.parse::<isize>()
-        )(input)
-    }
-
+fn parse_input(input: &str) -> IResult<&str, Vec<Rule<'_>>> {
     fn instruction(input: &str) -> IResult<&str, Instruction> {
         alt((
-            map(preceded(tag("inc "), number), Inc),
-            map(preceded(tag("dec "), number), Dec)
+            map(preceded(tag("inc "), signed), Inc),
+            map(preceded(tag("dec "), signed), Dec)
         ))(input)
     }
 
@@ -57,21 +50,21 @@ fn parse_input(input: &str) -> IResult<&str, Vec<Rule>> {
         ))(input)
     }
 
-    fn condition(input: &str) -> IResult<&str, Condition> {
+    fn condition(input: &str) -> IResult<&str, Condition<'_>> {
         map(
             preceded(
                 tag("if "),
                 tuple((
                     alpha1,
                     delimited(space1, comp, space1),
-                    number
+                    signed
                 ))
             ),
             |(register, comp, target)| Condition { comp, register, target }
         )(input)
     }
 
-    fn rule(input: &str) -> IResult<&str, Rule> {
+    fn rule(input: &str) -> IResult<&str, Rule<'_>> {
         map(
             tuple((
                 alpha1,
@@ -82,7 +75,7 @@ fn parse_input(input: &str) -> IResult<&str, Vec<Rule>> {
         )(input)
     }
 
-    separated_list1(newline, rule)(input)
+    lines(rule)(input)
 }
 
 fn part1(input: &str) -> isize {
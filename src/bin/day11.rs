@@ -1,41 +1,24 @@
-use std::ops::AddAssign;
 use itertools::Itertools;
 use adventofcode2017::build_main;
+use adventofcode2017::hex::{Hex, HexDirection};
 
-#[derive(Debug, Copy, Clone)]
-struct HexAxial(isize, isize);
-
-impl HexAxial {
-    fn num_steps(&self) -> isize {
-        let &HexAxial(q, r) = self;
-        (q.abs() + (q + r).abs() + r.abs()) / 2
-    }
-}
-
-impl AddAssign for HexAxial {
-    fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
-        self.1 += rhs.1;
-    }
-}
-
-struct Path<'a> {
-    cur: Option<HexAxial>,
-    directions: Vec<&'a str>,
+struct Path {
+    cur: Option<Hex>,
+    directions: Vec<HexDirection>,
     i: usize
 }
 
-impl<'a> Path<'a> {
-    fn new(directions: Vec<&'a str>) -> Path<'a> {
-        let cur = Some(HexAxial(0, 0));
+impl Path {
+    fn new(directions: Vec<HexDirection>) -> Path {
+        let cur = Some(Hex::origin());
         let i = 0;
 
         Path { cur, directions, i }
     }
 }
 
-impl Iterator for Path<'_> {
-    type Item = HexAxial;
+impl Iterator for Path {
+    type Item = Hex;
 
     fn next(&mut self) -> Option<Self::Item> {
         let result = self.cur;
@@ -45,17 +28,7 @@ impl Iterator for Path<'_> {
         }
         else {
             let step = self.directions[self.i];
-            let delta = match step {
-                "n" => HexAxial(0, -1),
-                "s" => HexAxial(0, 1),
-                "ne" => HexAxial(1, -1),
-                "sw" => HexAxial(-1, 1),
-                "se" => HexAxial(1, 0),
-                "nw" => HexAxial(-1, 0),
-                _ => unreachable!(),
-            };
-
-            self.cur.iter_mut().for_each( |c| *c += delta);
+            self.cur.iter_mut().for_each(|c| *c += step.to_hex());
             self.i += 1;
         }
 
@@ -63,17 +36,20 @@ impl Iterator for Path<'_> {
     }
 }
 
+fn parse_input(input: &str) -> Vec<HexDirection> {
+    input.trim().split(',')
+        .map(|s| s.parse::<HexDirection>().unwrap())
+        .collect_vec()
+}
+
 fn part1(input: &str) -> isize {
-    let directions = input.split(",").collect_vec();
-    let last = Path::new(directions).last().unwrap();
+    let last = Path::new(parse_input(input)).last().unwrap();
 
     last.num_steps()
 }
 
 fn part2(input: &str) -> isize {
-    let directions = input.split(",").collect_vec();
-
-    Path::new(directions)
+    Path::new(parse_input(input))
         .map(|hex| hex.num_steps())
         .max()
         .unwrap()
@@ -1,69 +1,57 @@
-use std::collections::HashSet;
 use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, newline};
-use nom::combinator::{eof, map_res};
+use nom::character::complete::digit1;
+use nom::combinator::all_consuming;
 use nom::IResult;
-use nom::multi::separated_list1;
-use nom::sequence::{preceded, terminated, tuple};
+use nom::sequence::{preceded, tuple};
 use adventofcode2017::build_main;
+use adventofcode2017::disjoint_set::DisjointSet;
+use adventofcode2017::graph::Graph;
+use adventofcode2017::parse::{comma_separated, lines, unsigned};
 
 fn parse_input(input: &str) -> IResult<&str, Vec<Vec<usize>>> {
-    fn number(input: &str) -> IResult<&str, usize> {
-        map_res(digit1, |s: &str| s.parse::<usize>())(input)
-    }
-
     fn line(input: &str) -> IResult<&str, Vec<usize>> {
         preceded(
             tuple((digit1, tag(" <-> "))),
-            separated_list1(tag(", "), number)
+            comma_separated(unsigned)
         )(input)
     }
 
-    terminated(separated_list1(newline, line), eof)(input)
+    all_consuming(lines(line))(input)
 }
 
-fn part1(input: &str) -> usize {
+fn build_graph(input: &str) -> Graph<usize> {
     let edges = parse_input(input).unwrap().1;
-    let mut stack = vec![0];
-    let mut seen = HashSet::new();
-    seen.insert(0);
+    let mut graph = Graph::new();
 
-    while let Some(id) = stack.pop() {
-        for &neighbor in edges[id].iter() {
-            if seen.insert(neighbor) {
-                stack.push(neighbor);
-            }
-        }
+    for (id, neighbors) in edges.into_iter().enumerate() {
+        graph.add_node(id);
+        neighbors.into_iter().for_each(|neighbor| graph.add_edge(id, neighbor));
     }
 
-    seen.len()
+    graph
+}
+
+fn part1(input: &str) -> usize {
+    build_graph(input).bfs(&0).count()
 }
 
 fn part2(input: &str) -> usize {
     let edges = parse_input(input).unwrap().1;
-    let mut seen = HashSet::new();
-
-    let mut count = 0;
-
-    for i in 0..edges.len() {
-        if !seen.contains(&i) {
-            count += 1;
-            let mut component = HashSet::new();
-            component.insert(i);
+    let mut sets = DisjointSet::new(edges.len());
 
-            let mut stack = vec![i];
-            while let Some(id) = stack.pop() {
-                for &neighbor in edges[id].iter() {
-                    if component.insert(neighbor) {
-                        stack.push(neighbor);
-                        seen.insert(neighbor);
-                    }
-                }
-            }
-        }
+    for (id, neighbors) in edges.into_iter().enumerate() {
+        neighbors.into_iter().for_each(|neighbor| { sets.union(id, neighbor); });
     }
 
-    count
+    sets.count()
+}
+
+fn part2_graph(input: &str) -> usize {
+    build_graph(input).components().len()
 }
 
-build_main!("day12.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
+build_main!(
+    "day12.txt",
+    "Part 1" => { "bfs" => part1 },
+    "Part 2" => { "union-find" => part2, "components" => part2_graph }
+);
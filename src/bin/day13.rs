@@ -1,11 +1,9 @@
-use itertools::Itertools;
 use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, newline};
-use nom::combinator::{map, map_res};
+use nom::combinator::map;
 use nom::IResult;
-use nom::multi::separated_list1;
 use nom::sequence::separated_pair;
 use adventofcode2017::build_main;
+use adventofcode2017::parse::{lines, unsigned};
 
 struct Scanner {
     depth: usize,
@@ -13,14 +11,9 @@ struct Scanner {
 }
 
 fn parse_input(input: &str) -> IResult<&str, Vec<Scanner>> {
-    fn number(input: &str) -> IResult<&str, usize> {
-        map_res(digit1, |s: &str| s.parse::<usize>())(input)
-    }
-
-    separated_list1(
-        newline,
+    lines(
         map(
-            separated_pair(number, tag(": "), number),
+            separated_pair(unsigned, tag(": "), unsigned),
             |(depth, range)| Scanner { depth, range }
         )
     )(input)
@@ -35,21 +28,12 @@ fn part1(input: &str) -> usize {
         .sum()
 }
 
-fn gcd(mut a: usize, mut b: usize) -> usize {
-    while b != 0 {
-        (a, b) = (b, a % b);
-    }
-
-    a
-}
-
 fn part2(input: &str) -> usize {
     let scanners = parse_input(input).unwrap().1;
 
-    (0..).filter(|&i| {
+    (0..).find(|&i| {
         scanners.iter().all(|scanner| (i + scanner.depth) % (2 * scanner.range - 2) != 0)
-    }).nth(0).unwrap()
-
+    }).unwrap()
 }
 
 build_main!("day13.txt", "Part 1" => part1, "Part 2" => part2);
@@ -1,6 +1,13 @@
-use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+use adventofcode2017::bitset::BitGrid;
 use adventofcode2017::build_main;
+use adventofcode2017::disjoint_set::DisjointSet;
+use adventofcode2017::geom::Point2;
+use adventofcode2017::graph::Graph;
+use adventofcode2017::grid::Grid;
 use adventofcode2017::knothash::knot_hash;
+use adventofcode2017::render::{save, write_ascii, write_pbm};
 
 fn part1(input: &str) -> usize {
     (0..128).map(|i| format!("{input}-{i}"))
@@ -9,65 +16,63 @@ fn part1(input: &str) -> usize {
         .sum()
 }
 
-fn neighbors((i, j): (usize, usize)) -> Vec<(usize, usize)> {
-    let mut result = vec![];
+fn disk(input: &str) -> BitGrid {
+    let mut disk = BitGrid::new(128, 128);
 
-    if i > 0 {
-        result.push((i-1, j));
-    }
-    if i < 127 {
-        result.push((i+1, j));
-    }
-    if j > 0 {
-        result.push((i, j-1));
-    }
-    if j < 127 {
-        result.push((i, j+1));
+    for i in 0..128 {
+        let hash = knot_hash(&format!("{input}-{i}"));
+        for j in 0..128 {
+            if hash[j / 8] & (1 << (7 - j % 8)) != 0 {
+                disk.set(Point2(i, j as isize), true);
+            }
+        }
     }
 
-    result
+    disk
 }
 
 fn part2(input: &str) -> usize {
-    let used: HashSet<(usize, usize)> = (0..128).map(|i| (i, format!("{input}-{i}")))
-        .flat_map(|(i, seed)| {
-            let hash = knot_hash(&seed);
-            (0..128).map(move |j| {
-                let index = j / 8;
-                let bit = j % 8;
-                let mask = 1 << (7 - bit);
-                ((i, j), hash[index] & mask != 0)
-            })
-        })
-        .filter(|(_, is_set)| *is_set)
-        .map(|(pos, _)| pos)
-        .collect();
-
-    let mut seen = HashSet::new();
-    let mut component_count = 0;
+    let used = disk(input);
+    let mut sets = DisjointSet::new(128 * 128);
 
-    for &(i, j) in used.iter() {
-        if seen.contains(&(i, j)) { continue; }
+    for Point2(i, j) in used.ones() {
+        let id = (i * 128 + j) as usize;
+        if used.get(Point2(i + 1, j)) {
+            sets.union(id, id + 128);
+        }
+        if used.get(Point2(i, j + 1)) {
+            sets.union(id, id + 1);
+        }
+    }
 
-        component_count += 1;
+    // Every unused square is left behind as a singleton set.
+    sets.count() - (128 * 128 - used.count_ones())
+}
 
-        seen.insert((i, j));
-        let mut component = HashSet::new();
-        component.insert((i, j));
-        let mut stack = Vec::new();
-        stack.push((i, j));
+fn part2_graph(input: &str) -> usize {
+    let used = disk(input);
+    let mut graph = Graph::new();
 
-        while let Some((u, v)) = stack.pop() {
-            for neighbor in neighbors((u, v)) {
-                if used.contains(&neighbor) && component.insert(neighbor) {
-                    seen.insert(neighbor);
-                    stack.push(neighbor);
-                }
-            }
-        }
+    for pos in used.ones() {
+        graph.add_node(pos);
+        pos.neighbors4().into_iter()
+            .filter(|&neighbor| used.get(neighbor))
+            .for_each(|neighbor| graph.add_edge(pos, neighbor));
     }
 
-    component_count
+    graph.components().len()
+}
+
+fn render_disk(input: &str, dir: &Path, _args: &[&str]) -> io::Result<()> {
+    let used = Grid::from(&disk(input));
+
+    save(dir.join("day14-disk.txt"), |out| write_ascii(out, &used, |&b| if b { '#' } else { '.' }))?;
+    save(dir.join("day14-disk.pbm"), |out| write_pbm(out, &used, 4, |&b| b))
 }
 
-build_main!("day14.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
+build_main!(
+    "day14.txt",
+    "Part 1" => { "popcount" => part1 },
+    "Part 2" => { "union-find" => part2, "components" => part2_graph };
+    render "disk" => render_disk
+);
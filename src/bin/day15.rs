@@ -1,6 +1,8 @@
 use itertools::Itertools;
 use adventofcode2017::build_main;
 
+const MODULUS: u64 = 2147483647;
+
 struct Generator {
     cur: u128,
     factor: u128
@@ -11,53 +13,96 @@ impl Iterator for Generator {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.cur *= self.factor;
-        self.cur %= 2147483647;
+        self.cur %= MODULUS as u128;
 
         Some((self.cur & 0xffff) as usize)
     }
 }
 
-fn part1(input: &str) -> usize {
-    let starts = input.lines()
+// 2^31 - 1 is a Mersenne prime, so reducing a product mod it only needs a shift, a mask and
+// at most one subtraction instead of a division.
+struct FastGenerator {
+    cur: u64,
+    factor: u64
+}
+
+impl Iterator for FastGenerator {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let product = self.cur * self.factor;
+        self.cur = (product & MODULUS) + (product >> 31);
+        if self.cur >= MODULUS {
+            self.cur -= MODULUS;
+        }
+
+        Some((self.cur & 0xffff) as usize)
+    }
+}
+
+fn parse_starts(input: &str) -> Vec<u64> {
+    input.lines()
         .map(|line| {
             line.split(' ')
-                .last().unwrap()
-                .parse::<u128>()
+                .next_back().unwrap()
+                .parse::<u64>()
                 .unwrap()
         })
-        .collect_vec();
-
-    let gen_a = Generator { cur: starts[0], factor: 16807 };
-    let gen_b = Generator { cur: starts[1], factor: 48271 };
+        .collect_vec()
+}
 
+fn count_matches(gen_a: impl Iterator<Item = usize>, gen_b: impl Iterator<Item = usize>, pairs: usize) -> usize {
     gen_a.zip(gen_b)
-        .take(40000000)
+        .take(pairs)
         .filter(|&(a, b)| a == b)
         .count()
 }
 
+fn part1(input: &str) -> usize {
+    let starts = parse_starts(input);
+
+    let gen_a = Generator { cur: starts[0] as u128, factor: 16807 };
+    let gen_b = Generator { cur: starts[1] as u128, factor: 48271 };
+
+    count_matches(gen_a, gen_b, 40000000)
+}
+
+fn part1_fast(input: &str) -> usize {
+    let starts = parse_starts(input);
+
+    let gen_a = FastGenerator { cur: starts[0], factor: 16807 };
+    let gen_b = FastGenerator { cur: starts[1], factor: 48271 };
+
+    count_matches(gen_a, gen_b, 40000000)
+}
+
 fn part2(input: &str) -> usize {
-    let starts = input.lines()
-        .map(|line| {
-            line.split(' ')
-                .last().unwrap()
-                .parse::<u128>()
-                .unwrap()
-        })
-        .collect_vec();
+    let starts = parse_starts(input);
 
-    let gen_a = Generator { cur: starts[0], factor: 16807 }
+    let gen_a = Generator { cur: starts[0] as u128, factor: 16807 }
         .filter(|&x| x % 4 == 0);
-    let gen_b = Generator { cur: starts[1], factor: 48271 }
+    let gen_b = Generator { cur: starts[1] as u128, factor: 48271 }
         .filter(|&x| x % 8 == 0);
 
-    gen_a.zip(gen_b)
-        .take(5000000)
-        .filter(|&(a, b)| a == b)
-        .count()
+    count_matches(gen_a, gen_b, 5000000)
+}
+
+fn part2_fast(input: &str) -> usize {
+    let starts = parse_starts(input);
+
+    let gen_a = FastGenerator { cur: starts[0], factor: 16807 }
+        .filter(|&x| x % 4 == 0);
+    let gen_b = FastGenerator { cur: starts[1], factor: 48271 }
+        .filter(|&x| x % 8 == 0);
+
+    count_matches(gen_a, gen_b, 5000000)
 }
 
-build_main!("day15.txt", "Part 1" => part1, "Part 2" => part2);
+build_main!(
+    "day15.txt",
+    "Part 1" => { "u128" => part1, "mersenne" => part1_fast },
+    "Part 2" => { "u128" => part2, "mersenne" => part2_fast }
+);
 
 #[cfg(test)]
 mod tests {
@@ -70,4 +115,12 @@ mod tests {
 
         gen_a.zip(gen_b).take(5).for_each(|(x, y)| println!("{x:10} {y:10}"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_fast_generator() {
+        let slow = Generator { cur: 65, factor: 16807 };
+        let fast = FastGenerator { cur: 65, factor: 16807 };
+
+        assert!(slow.zip(fast).take(100000).all(|(x, y)| x == y));
+    }
+}
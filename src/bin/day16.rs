@@ -1,13 +1,14 @@
 use crate::DanceMove::{Exchange, Partner, Spin};
 use adventofcode2017::build_main;
+use adventofcode2017::cycle::state_after;
+use adventofcode2017::parse::{comma_separated, unsigned};
+use adventofcode2017::permutation::Permutation;
 use itertools::Itertools;
 use nom::branch::alt;
-use nom::character::complete::{anychar, char, digit1};
-use nom::combinator::{map, map_res};
-use nom::multi::separated_list1;
+use nom::character::complete::{anychar, char};
+use nom::combinator::map;
 use nom::sequence::{preceded, separated_pair};
 use nom::IResult;
-use std::collections::HashSet;
 
 enum DanceMove {
     Spin(usize),
@@ -16,16 +17,11 @@ enum DanceMove {
 }
 
 fn parse_input(input: &str) -> IResult<&str, Vec<DanceMove>> {
-    fn number(input: &str) -> IResult<&str, usize> {
-        map_res(digit1, |s: &str| s.parse::<usize>())(input)
-    }
-
-    separated_list1(
-        char(','),
+    comma_separated(
         alt((
-            map(preceded(char('s'), number), Spin),
+            map(preceded(char('s'), unsigned), Spin),
             map(
-                preceded(char('x'), separated_pair(number, char('/'), number)),
+                preceded(char('x'), separated_pair(unsigned, char('/'), unsigned)),
                 |(a, b)| Exchange(a, b)
             ),
             map(
@@ -36,13 +32,11 @@ fn parse_input(input: &str) -> IResult<&str, Vec<DanceMove>> {
     )(input)
 }
 
-fn part1(input: &str) -> String {
-    let moves = parse_input(input).unwrap().1;
-
-    let mut programs = "abcdefghijklmnop".chars().collect_vec();
+fn dance(programs: &[char], moves: &[DanceMove]) -> Vec<char> {
+    let mut programs = programs.to_vec();
 
     for mov in moves {
-        match mov {
+        match *mov {
             Spin(x) => {
                 programs.rotate_right(x);
             },
@@ -57,72 +51,55 @@ fn part1(input: &str) -> String {
         }
     }
 
-    programs.into_iter().join("")
+    programs
 }
 
-fn cycles(perm: &[usize]) -> Vec<Vec<usize>> {
-    let mut result = Vec::new();
-    let mut seen = HashSet::new();
-
-    for i in 0..perm.len() {
-        if seen.contains(&i) {
-            continue;
-        }
-
-        let mut cycle = vec![i];
-        let mut j = perm[i];
-        while j != i {
-            seen.insert(j);
-            cycle.push(j);
-            j = perm[j];
-        }
-        result.push(cycle);
-    }
-
-    result
-}
+fn part1(input: &str) -> String {
+    let moves = parse_input(input).unwrap().1;
 
-fn gcd(mut a: usize, mut b: usize) -> usize {
-    while b != 0 {
-        (a, b) = (b, a % b);
-    }
-    a
-}
+    let programs = "abcdefghijklmnop".chars().collect_vec();
 
-fn lcm(a: usize, b: usize) -> usize {
-    (a * b) / gcd(a, b)
+    dance(&programs, &moves).into_iter().join("")
 }
 
 fn part2(input: &str) -> String {
     let moves = parse_input(input).unwrap().1;
 
-    let mut perm = (0..16).collect_vec();
+    // Spins and exchanges move positions while partner swaps rename programs, so the two
+    // commute and each can be raised to the billionth power on its own.
+    let mut positions = (0..16).collect_vec();
+    let mut names = (0..16).collect_vec();
 
     for mov in moves {
         match mov {
-            Spin(x) => perm.rotate_right(x),
-            Exchange(a, b) => perm.swap(a, b),
-            _ => ()
+            Spin(x) => positions.rotate_right(x),
+            Exchange(a, b) => positions.swap(a, b),
+            Partner(a, b) => {
+                let i = names.iter().position(|&n| n == (a as u8 - b'a') as usize).unwrap();
+                let j = names.iter().position(|&n| n == (b as u8 - b'a') as usize).unwrap();
+                names.swap(i, j);
+            }
         }
     }
 
-    let c = cycles(&perm);
-    let period = c.iter().map(|cycle| cycle.len())
-        .filter(|&n| n > 0)
-        .reduce(|x, y| lcm(x, y))
-        .unwrap();
+    let positions = Permutation::from_vec(positions).unwrap().pow(1000000000);
+    let names = Permutation::from_vec(names).unwrap().pow(1000000000);
 
-    let mut result = (0..16).collect_vec();
+    (0..16)
+        .map(|i| ((names[positions[i]] as u8) + b'a') as char)
+        .collect()
+}
 
-    for _ in 0..(1000000000 % period) {
-        for i in 0..16 {
-            result[i] = perm[result[i]];
-        }
-    }
+fn part2_cycle(input: &str) -> String {
+    let moves = parse_input(input).unwrap().1;
 
-    result.into_iter()
-        .map(|i| ((i as u8) + ('a' as u8)) as char)
-        .collect()
+    let programs = "abcdefghijklmnop".chars().collect_vec();
+
+    state_after(&programs, |cur| dance(cur, &moves), 1000000000).into_iter().join("")
 }
 
-build_main!("day16.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
+build_main!(
+    "day16.txt",
+    "Part 1" => { "default" => part1 },
+    "Part 2" => { "permutation" => part2, "cycle" => part2_cycle }
+);
\ No newline at end of file
@@ -1,12 +1,13 @@
 use std::collections::{HashMap, VecDeque};
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::character::complete::{anychar, char as ch, digit1, newline, space1};
-use nom::combinator::{map, map_res, opt, recognize};
+use nom::character::complete::{anychar, space1};
+use nom::combinator::map;
 use nom::IResult;
-use nom::multi::separated_list1;
-use nom::sequence::{pair, preceded, separated_pair};
+use nom::sequence::{preceded, separated_pair};
 use adventofcode2017::build_main;
+use adventofcode2017::parse::{lines, signed};
+use adventofcode2017::simulation::Simulation;
 use crate::Instruction::*;
 use crate::Operand::*;
 use crate::Step::*;
@@ -29,16 +30,9 @@ enum Instruction {
 }
 
 fn parse_input(input: &str) -> IResult<&str, Vec<Instruction>> {
-    fn number(input: &str) -> IResult<&str, isize> {
-        map_res(
-            recognize(pair(opt(ch('-')), digit1)),
-            |s: &str| s.parse::<isize>()
-        )(input)
-    }
-
     fn operand(input: &str) -> IResult<&str, Operand> {
         alt((
-            map(number, Literal),
+            map(signed, Literal),
             map(anychar, Register)
         ))(input)
     }
@@ -70,14 +64,16 @@ fn parse_input(input: &str) -> IResult<&str, Vec<Instruction>> {
         ))(input)
     }
 
-    separated_list1(newline, instruction)(input)
+    lines(instruction)(input)
 }
 
+#[derive(Clone)]
 struct Machine {
     registers: HashMap<char, isize>,
     instructions: Vec<Instruction>,
     cur_ptr: isize,
-    input_buffer: VecDeque<isize>
+    input_buffer: VecDeque<isize>,
+    steps: usize
 }
 
 #[derive(Eq, PartialEq)]
@@ -85,7 +81,6 @@ enum Step {
     Sent(isize),
     Waiting(char),
     Received(isize),
-    Done,
     Continue
 }
 
@@ -93,7 +88,7 @@ impl Machine {
     fn new(instructions: Vec<Instruction>, program_id: isize) -> Machine {
         let mut registers = HashMap::new();
         registers.insert('p', program_id);
-        Machine { registers, instructions, cur_ptr: 0, input_buffer: VecDeque::new() }
+        Machine { registers, instructions, cur_ptr: 0, input_buffer: VecDeque::new(), steps: 0 }
     }
 
     fn eval(&mut self, operand: Operand) -> isize {
@@ -102,10 +97,16 @@ impl Machine {
             Register(c) => *self.registers.entry(c).or_insert(0)
         }
     }
+}
 
-    fn step(&mut self) -> Step {
+impl Simulation for Machine {
+    type Output = Step;
+    type Snapshot = Machine;
+
+    /// Executes one instruction, or returns `None` once the pointer has left the program.
+    fn step(&mut self) -> Option<Step> {
         if self.cur_ptr < 0 || self.cur_ptr >= self.instructions.len() as isize {
-            return Done
+            return None
         }
 
         let mut advance = 1;
@@ -154,7 +155,20 @@ impl Machine {
         };
 
         self.cur_ptr += advance;
-        result
+        self.steps += 1;
+        Some(result)
+    }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+
+    fn snapshot(&self) -> Machine {
+        self.clone()
+    }
+
+    fn restore(&mut self, snapshot: Machine) {
+        *self = snapshot;
     }
 }
 
@@ -165,9 +179,9 @@ fn part1(input: &str) -> isize {
 
     loop {
         match machine.step() {
-            Done => panic!("Didn't ever enter waiting"),
-            Sent(value) => { last_value = Some(value); },
-            Waiting(reg) => {
+            None => panic!("Didn't ever enter waiting"),
+            Some(Sent(value)) => { last_value = Some(value); },
+            Some(Waiting(reg)) => {
                 if *machine.registers.entry(reg).or_insert(0) != 0 {
                     return last_value.unwrap()
                 }
@@ -188,21 +202,21 @@ fn part2(input: &str) -> usize {
         let m0_step = m0.step();
         let m1_step = m1.step();
 
-        if m0_step == Done && m1_step == Done {
+        if m0_step.is_none() && m1_step.is_none() {
             break;
         }
 
-        if let Waiting(_) = m0_step {
-            if let Waiting(_) = m1_step {
+        if let Some(Waiting(_)) = m0_step {
+            if let Some(Waiting(_)) = m1_step {
                 break;
             }
         }
 
-        if let Sent(x) = m0_step {
+        if let Some(Sent(x)) = m0_step {
             m1.input_buffer.push_back(x);
         }
 
-        if let Sent(x) = m1_step {
+        if let Some(Sent(x)) = m1_step {
             m0.input_buffer.push_back(x);
             p1_sent_count += 1;
         }
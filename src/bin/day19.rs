@@ -1,95 +1,49 @@
-use std::ops::{Add, AddAssign, Index};
-use itertools::Itertools;
+use std::io;
+use std::path::Path;
+use adventofcode2017::animation::Recorder;
 use adventofcode2017::build_main;
+use adventofcode2017::geom::{Direction, Point2, Turtle};
+use adventofcode2017::grid::Grid;
+use adventofcode2017::render::{save, write_ppm, Rgb};
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct Pair(isize, isize);
-
-impl Pair {
-    fn dot(&self, other: Pair) -> isize {
-        self.0 * other.0 + self.1 * other.1
-    }
-}
-
-impl AddAssign for Pair {
-    fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
-        self.1 += rhs.1;
-    }
-}
-
-impl Add for Pair {
-    type Output = Pair;
-    fn add(self, rhs: Self) -> Self::Output {
-        Pair(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-
-struct Diagram(Vec<Vec<char>>);
-
-impl Diagram {
-    fn get(&self, Pair(i, j): Pair) -> char {
-        self.0.get(i as usize).and_then(|row| row.get(j as usize).cloned()).unwrap_or(' ')
-    }
-}
-
-fn solve(input: &str) -> (String, usize) {
-    let diagram = Diagram(
-        input.lines()
-            .map(|line| line.chars().collect_vec())
-            .collect_vec()
-    );
-
-    let j_init = diagram.0[0].iter().position(|&c| c == '|').unwrap();
-    let mut pos = Pair(0, j_init as isize);
-    let mut dir = Pair(1, 0);
+/// Follows the route through `diagram`, calling `visit` with each position of the packet.
+fn walk(diagram: &Grid<char>, mut visit: impl FnMut(Point2)) -> (String, usize) {
+    let j_init = diagram.row(0).iter().position(|&c| c == '|').unwrap();
+    let mut turtle = Turtle::new(Point2(0, j_init as isize), Direction::South);
 
     let mut seen = Vec::new();
 
     let mut move_counts = 0;
 
     loop {
-        match diagram.get(pos) {
+        visit(turtle.pos);
+
+        match diagram.get_or(turtle.pos, ' ') {
             '+' => {
-                // Do we change direction?
-                dir = {
-                    if dir.0 == 0 {
-                        if diagram.get(pos + Pair(1, 0)) != ' ' {
-                            Pair(1, 0)
-                        }
-                        else {
-                            Pair(-1, 0)
-                        }
-                    }
-                    else {
-                        if diagram.get(pos + Pair(0, 1)) != ' ' {
-                            Pair(0, 1)
-                        }
-                        else {
-                            Pair(0, -1)
-                        }
-                    }
-                };
-                pos += dir;
-                move_counts += 1;
-            },
-            c if c.is_alphabetic() => {
-                seen.push(c);
-                pos += dir;
-                move_counts += 1;
+                if diagram.get_or(turtle.to_left(), ' ') != ' ' {
+                    turtle.turn_left();
+                }
+                else {
+                    turtle.turn_right();
+                }
             },
-            _ => {
-                pos += dir;
-                move_counts += 1;
-            }
+            c if c.is_alphabetic() => seen.push(c),
+            _ => ()
         }
 
-        if diagram.get(pos) == ' ' {
+        turtle.step();
+        move_counts += 1;
+
+        if diagram.get_or(turtle.pos, ' ') == ' ' {
             return (seen.into_iter().collect(), move_counts)
         }
     }
 }
 
+fn solve(input: &str) -> (String, usize) {
+    walk(&Grid::from_str(input, |c| c), |_| ())
+}
+
 fn part1(input: &str) -> String {
     solve(input).0
 }
@@ -98,4 +52,44 @@ fn part2(input: &str) -> usize {
     solve(input).1
 }
 
-build_main!("day19.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
+fn colour(c: char) -> Rgb {
+    match c {
+        ' ' => Rgb::BLACK,
+        '+' => Rgb::YELLOW,
+        c if c.is_alphabetic() => Rgb::RED,
+        _ => Rgb::gray(160)
+    }
+}
+
+fn render_diagram(input: &str, dir: &Path, _args: &[&str]) -> io::Result<()> {
+    let diagram = Grid::from_str(input, |c| c);
+
+    save(dir.join("day19-diagram.ppm"), |out| write_ppm(out, &diagram, 4, |&c| colour(c)))
+}
+
+fn animate_packet(input: &str, dir: &Path, _args: &[&str]) -> io::Result<()> {
+    const MAX_FRAMES: usize = 500;
+
+    let diagram = Grid::from_str(input, |c| c);
+    let mut path = Vec::new();
+    let (_, steps) = walk(&diagram, |pos| path.push(pos));
+
+    let mut canvas = diagram.map(|&c| colour(c));
+    let mut recorder = Recorder::new(dir, "day19-packet")
+        .every(steps.div_ceil(MAX_FRAMES).max(1))
+        .max_frames(MAX_FRAMES)
+        .scale(4);
+
+    for pos in path {
+        canvas[pos] = Rgb::GREEN;
+        recorder.step(|| {
+            let mut frame = canvas.clone();
+            frame[pos] = Rgb::WHITE;
+            frame
+        }, |&c| c)?;
+    }
+
+    recorder.finish().map(drop)
+}
+
+build_main!("day19.txt", "Part 1" => part1, "Part 2" => part2; render "diagram" => render_diagram, "packet" => animate_packet);
\ No newline at end of file
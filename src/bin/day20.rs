@@ -1,42 +1,15 @@
 use adventofcode2017::build_main;
+use adventofcode2017::geom::Point3;
+use adventofcode2017::math::perfect_sqrt;
+use adventofcode2017::parse::{comma_separated, lines, signed};
 use itertools::Itertools;
 use nom::bytes::complete::tag;
-use nom::character::complete::{char, digit1, newline, space0};
-use nom::combinator::{map, map_res, opt, recognize};
-use nom::multi::separated_list1;
-use nom::sequence::{delimited, pair, preceded, terminated, tuple};
+use nom::character::complete::{char, space0};
+use nom::combinator::map;
+use nom::sequence::{delimited, preceded, terminated, tuple};
 use nom::IResult;
 use std::collections::{HashMap, HashSet};
-use std::ops::{Add, AddAssign, Mul, Sub};
-
-fn isqrt(n: isize) -> Option<isize> {
-    if n < 0 {
-        None
-    }
-    else {
-        let mut low = 0;
-        let mut high = n;
-
-        while low < high {
-            let mid = low + (high - low) / 2;
-
-            let sq = mid * mid;
-
-            if sq <= n && sq + 2*mid + 1 > n {
-                low = mid;
-                high = mid;
-            }
-            else if mid * mid < n {
-                low = mid + 1;
-            }
-            else {
-                high = mid - 1;
-            }
-        }
-
-        if low * low == n { Some(low) } else { None }
-    }
-}
+use std::ops::Sub;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 enum Solutions {
@@ -78,9 +51,10 @@ fn nonneg_int_quadratic_sols(a: isize, b: isize, c: isize) -> Solutions {
     }
     else {
         let disc = b*b - 4*a*c;
-        match isqrt(disc) {
+        match u64::try_from(disc).ok().and_then(perfect_sqrt) {
             None => Solutions::Finite(vec![]),
             Some(disc_sqrt) => {
+                let disc_sqrt = disc_sqrt as isize;
                 let opts = [-b + disc_sqrt, -b - disc_sqrt].into_iter()
                     .filter(|&num| num % (2 * a) == 0)
                     .map(|num| num / (2 * a))
@@ -93,47 +67,8 @@ fn nonneg_int_quadratic_sols(a: isize, b: isize, c: isize) -> Solutions {
     }
 }
 
-#[derive(Copy, Clone, Hash, Eq, PartialEq)]
-struct Triple(isize, isize, isize);
-
-impl Triple {
-    fn norm(&self) -> isize {
-        self.0.abs() + self.1.abs() + self.2.abs()
-    }
-}
-
-impl Add for Triple {
-    type Output = Self;
-    fn add(self, other: Self) -> Self {
-        Self(self.0 + other.0, self.1 + other.1, self.2 + other.2)
-    }
-}
-
-impl AddAssign for Triple {
-    fn add_assign(&mut self, other: Self) {
-        self.0 += other.0;
-        self.1 += other.1;
-        self.2 += other.2;
-    }
-}
-
-impl Sub for Triple {
-    type Output = Triple;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Triple(self.0 - rhs.0, self.1 - rhs.1, self.2 - rhs.2)
-    }
-}
-
-impl Mul<isize> for Triple {
-    type Output = Triple;
-    fn mul(self, rhs: isize) -> Self::Output {
-        Triple(self.0 * rhs, self.1 * rhs, self.2 * rhs)
-    }
-}
-
 #[derive(Copy, Clone)]
-struct Particle { p: Triple, v: Triple, a: Triple }
+struct Particle { p: Point3, v: Point3, a: Point3 }
 
 impl Sub for Particle {
     type Output = Particle;
@@ -145,9 +80,9 @@ impl Sub for Particle {
 
 impl Particle {
     fn origin_hits(&self) -> Solutions {
-        let Triple(p_x, p_y, p_z) = self.p;
-        let Triple(v_x, v_y, v_z) = self.v;
-        let Triple(a_x, a_y, a_z) = self.a;
+        let Point3(p_x, p_y, p_z) = self.p;
+        let Point3(v_x, v_y, v_z) = self.v;
+        let Point3(a_x, a_y, a_z) = self.a;
 
         let x_sols = nonneg_int_quadratic_sols(a_x, 2*v_x + a_x, 2*p_x);
         let y_sols = nonneg_int_quadratic_sols(a_y, 2*v_y + a_y, 2*p_y);
@@ -165,21 +100,14 @@ impl Particle {
 }
 
 fn parse_input(input: &str) -> IResult<&str, Vec<Particle>> {
-    fn number(input: &str) -> IResult<&str, isize> {
-        map_res(
-            recognize(pair(opt(char('-')), digit1)),
-            |s: &str| s.parse::<isize>()
-        )(input)
-    }
-
-    fn triple(input: &str) -> IResult<&str, Triple> {
+    fn triple(input: &str) -> IResult<&str, Point3> {
         map(
             delimited(
                 terminated(char('<'), space0),
-                separated_list1(char(','), number),
+                comma_separated(signed),
                 char('>')
             ),
-            |v| Triple(v[0], v[1], v[2])
+            |v| Point3(v[0], v[1], v[2])
         )(input)
     }
 
@@ -194,7 +122,7 @@ fn parse_input(input: &str) -> IResult<&str, Vec<Particle>> {
         )(input)
     }
 
-    separated_list1(newline, particle)(input)
+    lines(particle)(input)
 }
 
 fn part1(input: &str) -> usize {
@@ -202,7 +130,7 @@ fn part1(input: &str) -> usize {
 
     particles.into_iter()
         .position_min_by_key(|p| {
-            (p.a.norm(), p.v.norm(), p.p.norm())
+            (p.a.manhattan(), p.v.manhattan(), p.p.manhattan())
         })
         .unwrap()
 }
@@ -12,8 +12,8 @@
     ghi  ifc  ihg
  */
 use std::collections::HashMap;
-use std::ops::{Index, IndexMut};
-use itertools::Itertools;
+use std::io;
+use std::path::Path;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::{char, newline};
@@ -21,95 +21,47 @@ use nom::combinator::{all_consuming, map, value};
 use nom::IResult;
 use nom::multi::{many1, separated_list1};
 use nom::sequence::separated_pair;
+use adventofcode2017::bitset::BitGrid;
 use adventofcode2017::build_main;
+use adventofcode2017::grid::Grid;
+use adventofcode2017::render::{save, write_ascii, write_pbm};
 
-fn flip(data: &Vec<Vec<bool>>) -> Vec<Vec<bool>> {
-    (0..data.len()).map(|i| {
-        (0..data.len()).map(|j| data[i][data.len() - 1 - j]).collect_vec()
-    }).collect_vec()
-}
-
-fn rotate(data: &Vec<Vec<bool>>) -> Vec<Vec<bool>> {
-    (0..data.len()).map(|i| {
-        (0..data.len()).map(|j| data[j][data.len() - 1- i]).collect_vec()
-    }).collect_vec()
-}
-
-fn all_symmetries(data: &Vec<Vec<bool>>) -> Vec<Vec<Vec<bool>>> {
+fn all_symmetries(grid: &BitGrid) -> Vec<BitGrid> {
     let mut result = Vec::new();
 
-    let mut cur = data.clone();
+    let mut cur = grid.clone();
 
     for _ in 0..4 {
-        result.push(cur.clone());
-        result.push(flip(&cur));
-        cur = rotate(&cur);
+        result.push(cur.flip_horizontal());
+        let next = cur.rotate_right();
+        result.push(cur);
+        cur = next;
     }
 
     result
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
-struct Grid(Vec<Vec<bool>>);
-
-impl Grid {
-    // fn print(&self) {
-    //     self.0.iter().for_each(|row| {
-    //         let s: String = row.iter().map(|&b| if b { '#' } else { '.' }).collect();
-    //         println!("{s}");
-    //     });
-    // }
-
-    fn copy_from(&mut self, other: &Grid, start_i: usize, start_j: usize) {
-        (0..other.0.len()).cartesian_product(0..other.0.len()).for_each(|(i, j)| {
-            self[(start_i + i, start_j + j)] = other[(i, j)]
-        })
-    }
-
-    fn subgrid(&self, start_i: usize, start_j: usize, size: usize) -> Grid {
-        let result = (0..size).map(|i| {
-            (0..size).map(|j| self[(start_i + i, start_j + j)]).collect_vec()
-        }).collect_vec();
+fn apply_rules(grid: &BitGrid, rules: &HashMap<BitGrid, BitGrid>) -> BitGrid {
+    let size = grid.width();
+    let chunk_size = if size.is_multiple_of(2) { 2 } else { 3 };
+    let new_chunk_size = chunk_size + 1;
+    let new_size = (size / chunk_size) * new_chunk_size;
 
-        Grid(result)
-    }
+    let mut result = BitGrid::new(new_size, new_size);
 
-    fn apply_rules(&self, rules: &HashMap<Grid, Grid>) -> Grid {
-        let chunk_size = if self.0.len() % 2 == 0 { 2 } else { 3 };
-        let new_chunk_size = if chunk_size == 2 { 3 } else { 4 };
-        let new_size =
-            if chunk_size == 2 { (self.0.len() / 2) * 3 }
-            else { (self.0.len() / 3) * 4 };
-
-        let mut result = Grid(vec![vec![false; new_size]; new_size]);
-
-        for i in 0..self.0.len() / chunk_size {
-            for j in 0..self.0.len() / chunk_size {
-                let subgrid = self.subgrid(i * chunk_size, j * chunk_size, chunk_size);
-                let new_subgrid = rules.get(&subgrid).unwrap();
-                result.copy_from(&new_subgrid, i * new_chunk_size, j * new_chunk_size);
-            }
+    for i in 0..size / chunk_size {
+        for j in 0..size / chunk_size {
+            let subgrid = grid.subgrid(i * chunk_size, j * chunk_size, chunk_size, chunk_size);
+            result.blit(&rules[&subgrid], i * new_chunk_size, j * new_chunk_size);
         }
-
-        result
     }
-}
 
-impl Index<(usize, usize)> for Grid {
-    type Output = bool;
-
-    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
-        &self.0[i][j]
-    }
+    result
 }
 
-impl IndexMut<(usize, usize)> for Grid {
-    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
-        &mut self.0[i][j]
-    }
-}
+type Rule = (BitGrid, BitGrid);
 
-fn parse_input(input: &str) -> IResult<&str, Vec<(Grid, Grid)>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<Rule>> {
     fn pixel(input: &str) -> IResult<&str, bool> {
         alt((
             value(true, char('#')),
@@ -117,36 +69,39 @@ fn parse_input(input: &str) -> IResult<&str, Vec<(Grid, Grid)>> {
         ))(input)
     }
 
-    fn grid(input: &str) -> IResult<&str, Grid> {
+    fn grid(input: &str) -> IResult<&str, BitGrid> {
         map(
             separated_list1(char('/'), many1(pixel)),
-            |data| Grid(data)
+            BitGrid::from_rows
         )(input)
     }
 
-    fn line(input: &str) -> IResult<&str, (Grid, Grid)> {
+    fn line(input: &str) -> IResult<&str, Rule> {
         separated_pair(grid, tag(" => "), grid)(input)
     }
 
     all_consuming(separated_list1(newline, line))(input)
 }
 
-fn solve(input: &str, iterations: usize) -> usize {
-    let rules: HashMap<Grid, Grid> = parse_input(input).unwrap().1.into_iter()
-        .flat_map(|(input, ref output)| {
-            all_symmetries(&input.0).into_iter()
-                .map(move |sym| (Grid(sym), output.clone()))
-                .collect_vec()
+fn grow(input: &str, iterations: usize) -> BitGrid {
+    let rules: HashMap<BitGrid, BitGrid> = parse_input(input).unwrap().1.into_iter()
+        .flat_map(|(input, output)| {
+            all_symmetries(&input).into_iter()
+                .map(move |sym| (sym, output.clone()))
         })
         .collect();
 
-    let mut grid = Grid(vec![vec![false, true, false], vec![false, false, true], vec![true, true, true]]);
+    let mut grid = BitGrid::from_str(".#.\n..#\n###", |c| c == '#');
 
     for _ in 0..iterations {
-        grid = grid.apply_rules(&rules);
+        grid = apply_rules(&grid, &rules);
     }
 
-    grid.0.into_iter().flatten().filter(|&b| b).count()
+    grid
+}
+
+fn solve(input: &str, iterations: usize) -> usize {
+    grow(input, iterations).count_ones()
 }
 
 fn part1(input: &str) -> usize {
@@ -157,4 +112,11 @@ fn part2(input: &str) -> usize {
     solve(input, 18)
 }
 
-build_main!("day21.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
+fn render_fractal(input: &str, dir: &Path, _args: &[&str]) -> io::Result<()> {
+    let fractal = Grid::from(&grow(input, 5));
+
+    save(dir.join("day21-fractal.txt"), |out| write_ascii(out, &fractal, |&b| if b { '#' } else { '.' }))?;
+    save(dir.join("day21-fractal.pbm"), |out| write_pbm(out, &fractal, 8, |&b| b))
+}
+
+build_main!("day21.txt", "Part 1" => part1, "Part 2" => part2; render "fractal" => render_fractal);
\ No newline at end of file
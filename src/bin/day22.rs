@@ -1,72 +1,119 @@
-use std::collections::{HashMap, HashSet};
-use std::ops::AddAssign;
-use itertools::Itertools;
+use std::io;
+use std::path::Path;
+use nom::combinator::all_consuming;
+use adventofcode2017::animation::Recorder;
 use adventofcode2017::build_main;
+use adventofcode2017::checkpoint::{path_for, run_checkpointed, Checkpoint, Record};
+use adventofcode2017::geom::{Direction, Point2, Turtle};
+use adventofcode2017::grid::{Grid, InfiniteGrid};
+use adventofcode2017::parse::grid;
+use adventofcode2017::render::{save, write_ascii, write_pbm, write_ppm, Rgb};
+use adventofcode2017::simulation::Simulation;
 use crate::State::{Flagged, Infected, Weakened};
 
-#[derive(Eq, PartialEq, Hash, Copy, Clone)]
-struct Pair(isize, isize);
+/// The infected nodes of the map, which must be rectangular.
+fn parse_input(input: &str) -> Grid<bool> {
+    let cell = |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None
+    };
 
-impl AddAssign for Pair {
-    fn add_assign(&mut self, rhs: Self) {
-        self.0 += rhs.0;
-        self.1 += rhs.1;
-    }
+    all_consuming(grid(cell))(input).unwrap().1
+}
+
+fn center(grid: &Grid<bool>) -> Point2 {
+    Point2((grid.height() as isize - 1) / 2, (grid.width() as isize - 1) / 2)
 }
 
+#[derive(Clone)]
 struct Board1 {
-    infected: HashSet<Pair>,
-    carrier_pos: Pair,
-    carrier_dir: Pair
+    infections: usize,
+    infected: InfiniteGrid<bool>,
+    carrier: Turtle,
+    steps: usize
 }
 
 impl Board1 {
     fn new(input: &str) -> Board1 {
-        let data = input.lines()
-            .map(|row| row.chars().map(|c| c == '#').collect_vec())
-            .collect_vec();
-        let rows = data.len() as isize;
-        let cols = data[0].len() as isize;
-        let carrier_pos = Pair((rows - 1) / 2, (cols - 1) / 2);
-        let carrier_dir = Pair(-1, 0);
-
-        let infected = data.into_iter().enumerate()
-            .flat_map(|(i, row)| {
-                row.into_iter().enumerate()
-                    .filter_map(move |(j, b)| {
-                        if b { Some(Pair(i as isize, j as isize)) } else { None }
-                    })
-            })
-            .collect();
+        let grid = parse_input(input);
+        let carrier = Turtle::new(center(&grid), Direction::North);
+
+        let infected = InfiniteGrid::from_grid(&grid, Point2(0, 0), false);
 
-        Board1 { infected, carrier_pos, carrier_dir }
+        Board1 { infections: 0, infected, carrier, steps: 0 }
     }
 }
 
-impl Iterator for Board1 {
-    type Item = bool;
+impl Simulation for Board1 {
+    type Output = bool;
+    type Snapshot = Board1;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let Pair(x, y) = self.carrier_dir;
-        let did_infect = if self.infected.contains(&self.carrier_pos) {
-            self.carrier_dir = Pair(y, -x);
-            self.infected.remove(&self.carrier_pos);
+    fn step(&mut self) -> Option<bool> {
+        let did_infect = if self.infected[self.carrier.pos] {
+            self.carrier.turn_right();
             false
         }
         else {
-            self.carrier_dir = Pair(-y, x);
-            self.infected.insert(self.carrier_pos);
+            self.carrier.turn_left();
             true
         };
 
-        self.carrier_pos += self.carrier_dir;
+        self.infected.set(self.carrier.pos, did_infect);
+        self.infections += did_infect as usize;
+        self.carrier.step();
+        self.steps += 1;
 
         Some(did_infect)
     }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+
+    fn snapshot(&self) -> Board1 {
+        self.clone()
+    }
+
+    fn restore(&mut self, snapshot: Board1) {
+        *self = snapshot;
+    }
+}
+
+impl Checkpoint for Board1 {
+    const KIND: &'static str = "day22-board1";
+
+    fn to_record(&self) -> Record {
+        let (origin, grid) = self.infected.to_grid();
+
+        let mut record = Record::new(Self::KIND);
+        record.put("steps", self.steps);
+        record.put("infections", self.infections);
+        record.put_point("carrier", self.carrier.pos);
+        record.put("heading", self.carrier.heading);
+        record.put_point("origin", origin);
+        record.put_grid("row", &grid, |&b| if b { '#' } else { '.' });
+        record
+    }
+
+    fn from_record(record: &Record) -> Result<Board1, String> {
+        let grid = record.get_grid("row", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None
+        })?;
+
+        Ok(Board1 {
+            infections: record.get("infections")?,
+            infected: InfiniteGrid::from_grid(&grid, record.get_point("origin")?, false),
+            carrier: Turtle::new(record.get_point("carrier")?, record.get("heading")?),
+            steps: record.get("steps")?
+        })
+    }
 }
 
 fn part1(input: &str) -> usize {
-    Board1::new(input)
+    Board1::new(input).outputs()
         .take(10000)
         .filter(|&infected| infected)
         .count()
@@ -75,75 +122,179 @@ fn part1(input: &str) -> usize {
 #[derive(Copy, Clone, Eq, PartialEq)]
 enum State { Weakened, Infected, Flagged }
 
+#[derive(Clone)]
 struct Board2 {
-    infected: HashMap<Pair, State>,
-    carrier_pos: Pair,
-    carrier_dir: Pair
+    infections: usize,
+    infected: InfiniteGrid<Option<State>>,
+    carrier: Turtle,
+    steps: usize
 }
 
 impl Board2 {
     fn new(input: &str) -> Board2 {
-        let data = input.lines()
-            .map(|row| row.chars().map(|c| c == '#').collect_vec())
-            .collect_vec();
-        let rows = data.len() as isize;
-        let cols = data[0].len() as isize;
-        let carrier_pos = Pair((rows - 1) / 2, (cols - 1) / 2);
-        let carrier_dir = Pair(-1, 0);
-
-        let infected = data.into_iter().enumerate()
-            .flat_map(|(i, row)| {
-                row.into_iter().enumerate()
-                    .filter_map(move |(j, b)| {
-                        if b { Some((Pair(i as isize, j as isize), Infected)) } else { None }
-                    })
-            })
-            .collect();
+        let grid = parse_input(input);
+        let carrier = Turtle::new(center(&grid), Direction::North);
 
-        Board2 { infected, carrier_pos, carrier_dir }
+        let infected = InfiniteGrid::from_grid(&grid.map(|&b| b.then_some(Infected)), Point2(0, 0), None);
+
+        Board2 { infections: 0, infected, carrier, steps: 0 }
     }
 }
 
-impl Iterator for Board2 {
-    type Item = Option<State>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let Pair(x, y) = self.carrier_dir;
+impl Simulation for Board2 {
+    type Output = Option<State>;
+    type Snapshot = Board2;
 
-        let result = match self.infected.get(&self.carrier_pos) {
+    fn step(&mut self) -> Option<Option<State>> {
+        let result = match self.infected[self.carrier.pos] {
             None => {
-                self.carrier_dir = Pair(-y, x);
+                self.carrier.turn_left();
                 Some(Weakened)
             },
             Some(Weakened) => {
                 Some(Infected)
             },
             Some(Infected) => {
-                self.carrier_dir = Pair(y, -x);
+                self.carrier.turn_right();
                 Some(Flagged)
             },
             Some(Flagged) => {
-                self.carrier_dir = Pair(-x, -y);
+                self.carrier.reverse();
                 None
             }
         };
 
-        match result {
-            None => { self.infected.remove(&self.carrier_pos); },
-            Some(x) => { self.infected.insert(self.carrier_pos, x); }
-        }
-
-        self.carrier_pos += self.carrier_dir;
+        self.infected.set(self.carrier.pos, result);
+        self.infections += (result == Some(Infected)) as usize;
+        self.carrier.step();
+        self.steps += 1;
 
         Some(result)
     }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+
+    fn snapshot(&self) -> Board2 {
+        self.clone()
+    }
+
+    fn restore(&mut self, snapshot: Board2) {
+        *self = snapshot;
+    }
+}
+
+impl Checkpoint for Board2 {
+    const KIND: &'static str = "day22-board2";
+
+    fn to_record(&self) -> Record {
+        let (origin, grid) = self.infected.to_grid();
+
+        let mut record = Record::new(Self::KIND);
+        record.put("steps", self.steps);
+        record.put("infections", self.infections);
+        record.put_point("carrier", self.carrier.pos);
+        record.put("heading", self.carrier.heading);
+        record.put_point("origin", origin);
+        record.put_grid("row", &grid, |&state| match state {
+            None => '.',
+            Some(Weakened) => 'W',
+            Some(Infected) => '#',
+            Some(Flagged) => 'F'
+        });
+        record
+    }
+
+    fn from_record(record: &Record) -> Result<Board2, String> {
+        let grid = record.get_grid("row", |c| match c {
+            '.' => Some(None),
+            'W' => Some(Some(Weakened)),
+            '#' => Some(Some(Infected)),
+            'F' => Some(Some(Flagged)),
+            _ => None
+        })?;
+
+        Ok(Board2 {
+            infections: record.get("infections")?,
+            infected: InfiniteGrid::from_grid(&grid, record.get_point("origin")?, None),
+            carrier: Turtle::new(record.get_point("carrier")?, record.get("heading")?),
+            steps: record.get("steps")?
+        })
+    }
 }
 
 fn part2(input: &str) -> usize {
-    Board2::new(input).take(10000000)
-        .filter_map(|state| state)
+    Board2::new(input).outputs().take(10000000)
+        .flatten()
         .filter(|&s| s == Infected)
         .count()
 }
 
-build_main!("day22.txt", "Part 1" => part1, "Part 2" => part2);
\ No newline at end of file
+/// Like `part2`, but saving progress in `dir` every million bursts and resuming from there if
+/// interrupted.
+fn checkpoint_part2(input: &str, dir: &Path) -> Result<Vec<(&'static str, String)>, String> {
+    let mut board = Board2::new(input);
+    let path = path_for(dir, "day22-part2", input);
+
+    run_checkpointed(&mut board, 10000000, &path, 1000000).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(vec![("Part 2", board.infections.to_string())])
+}
+
+fn render_infection(input: &str, dir: &Path, _args: &[&str]) -> io::Result<()> {
+    let mut board1 = Board1::new(input);
+    board1.run_for(10000);
+    let (_, infected) = board1.infected.to_grid();
+
+    save(dir.join("day22-part1.txt"), |out| write_ascii(out, &infected, |&b| if b { '#' } else { '.' }))?;
+    save(dir.join("day22-part1.pbm"), |out| write_pbm(out, &infected, 4, |&b| b))?;
+
+    let mut board2 = Board2::new(input);
+    board2.run_for(10000000);
+    let (_, states) = board2.infected.to_grid();
+
+    save(dir.join("day22-part2.ppm"), |out| {
+        write_ppm(out, &states, 2, |&state| match state {
+            None => Rgb::BLACK,
+            Some(Weakened) => Rgb::YELLOW,
+            Some(Infected) => Rgb::RED,
+            Some(Flagged) => Rgb::BLUE
+        })
+    })
+}
+
+fn animate_carrier(input: &str, dir: &Path, _args: &[&str]) -> io::Result<()> {
+    const RADIUS: isize = 40;
+
+    let mut board = Board1::new(input);
+    let start = board.carrier.pos;
+    let mut recorder = Recorder::new(dir, "day22-carrier").every(25).max_frames(400).scale(4);
+
+    let frame = |board: &Board1| {
+        let cells = (-RADIUS..=RADIUS)
+            .flat_map(|i| (-RADIUS..=RADIUS).map(move |j| start + Point2(i, j)))
+            .map(|pos| match (pos == board.carrier.pos, board.infected[pos]) {
+                (true, _) => Rgb::GREEN,
+                (false, true) => Rgb::RED,
+                (false, false) => Rgb::BLACK
+            })
+            .collect();
+
+        Grid::from_vec(2 * RADIUS as usize + 1, 2 * RADIUS as usize + 1, cells)
+    };
+
+    for _ in 0..10000 {
+        recorder.step(|| frame(&board), |&c| c)?;
+        board.step();
+    }
+
+    recorder.finish().map(drop)
+}
+
+build_main!(
+    "day22.txt",
+    "Part 1" => { "default" => part1 },
+    "Part 2" => { "default" => part2 };
+    render "infection" => render_infection, "carrier" => animate_carrier;
+    checkpoint checkpoint_part2
+);
\ No newline at end of file
@@ -1,11 +1,12 @@
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::character::complete::{anychar, char, digit1, newline, space1};
-use nom::combinator::{all_consuming, map, map_res, opt, recognize};
+use nom::character::complete::{anychar, space1};
+use nom::combinator::{all_consuming, map};
 use nom::IResult;
-use nom::multi::separated_list1;
-use nom::sequence::{pair, preceded, separated_pair};
+use nom::sequence::{preceded, separated_pair};
 use adventofcode2017::build_main;
+use adventofcode2017::math::is_prime;
+use adventofcode2017::parse::{lines, signed};
 use crate::Instruction::{Jnz, Mul, Set, Sub};
 use crate::Operand::{Literal, Register};
 
@@ -75,16 +76,9 @@ fn parse_input(input: &str) -> IResult<&str, Vec<Instruction>> {
         map(anychar, |c| c as usize - 'a' as usize)(input)
     }
 
-    fn number(input: &str) -> IResult<&str, isize> {
-        map_res(
-            recognize(pair(opt(char('-')), digit1)),
-            |s: &str| s.parse::<isize>()
-        )(input)
-    }
-
     fn operand(input: &str) -> IResult<&str, Operand> {
         alt((
-            map(number, Literal),
+            map(signed, Literal),
             map(register, Register)
         ))(input)
     }
@@ -110,7 +104,7 @@ fn parse_input(input: &str) -> IResult<&str, Vec<Instruction>> {
         ))(input)
     }
 
-    all_consuming(separated_list1(newline, instruction))(input)
+    all_consuming(lines(instruction))(input)
 }
 
 fn part1(input: &str) -> usize {
@@ -119,9 +113,8 @@ fn part1(input: &str) -> usize {
     let mut mults = 0;
 
     for (instr, _) in machine {
-        match instr {
-            Mul { .. } => mults += 1,
-            _ => ()
+        if let Mul { .. } = instr {
+            mults += 1;
         }
     }
 
@@ -173,21 +166,11 @@ fn part1(input: &str) -> usize {
 31. jnz 1 -23 -> label2
  */
 
-fn is_composite(n: usize) -> bool {
-    if n % 2 == 0 {
-        return true;
-    }
-
-    let max = (n as f64).sqrt().ceil() as usize;
-
-    (3..=max).step_by(2).any(|i| n % i == 0)
-}
-
 fn part2(_: &str) -> usize {
-    let mut b = 84*100 + 100000;
+    let b = 84*100 + 100000;
     let c = b + 17000;
     (b..=c).step_by(17)
-        .filter(|&n| is_composite(n))
+        .filter(|&n| !is_prime(n))
         .count()
 }
 
@@ -1,12 +1,22 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use itertools::Itertools;
 use nom::branch::alt;
 use nom::bytes::complete::{is_not, tag};
-use nom::character::complete::{anychar, char as ch, digit1, multispace1, newline, space0};
-use nom::combinator::{all_consuming, map, map_res, value};
+use nom::character::complete::{anychar, char as ch, multispace1, newline, space0};
+use nom::combinator::{all_consuming, map, value};
 use nom::IResult;
 use nom::multi::separated_list1;
 use nom::sequence::{delimited, pair, preceded, tuple};
+use adventofcode2017::animation::Recorder;
+use adventofcode2017::bitset::BitSet;
 use adventofcode2017::build_main;
+use adventofcode2017::checkpoint::{path_for, run_checkpointed, Checkpoint, Record};
+use adventofcode2017::grid::Grid;
+use adventofcode2017::parse::unsigned;
+use adventofcode2017::render::Rgb;
+use adventofcode2017::simulation::Simulation;
 
 struct Result {
     to_write: bool,
@@ -16,40 +26,129 @@ struct Result {
 
 struct Machine {
     rules: HashMap<char, (Result, Result)>,
-    tape: HashSet<isize>,
+    tape: BitSet,
     state: char,
     steps_remaining: usize,
-    cur_position: isize
+    cur_position: isize,
+    steps: usize
 }
 
-impl Iterator for Machine {
-    type Item = usize;
+/// Everything about a `Machine` except its rules, which never change.
+#[derive(Clone)]
+struct MachineState {
+    tape: BitSet,
+    state: char,
+    steps_remaining: usize,
+    cur_position: isize,
+    steps: usize
+}
+
+/// Interleaves the two halves of the tape, 0, -1, 1, -2, ..., so it can live in a `BitSet`.
+fn slot(position: isize) -> usize {
+    if position >= 0 {
+        2 * position as usize
+    }
+    else {
+        2 * (-position) as usize - 1
+    }
+}
+
+/// The inverse of `slot`.
+fn position(slot: usize) -> isize {
+    if slot.is_multiple_of(2) {
+        (slot / 2) as isize
+    }
+    else {
+        -(slot.div_ceil(2) as isize)
+    }
+}
+
+impl Checkpoint for MachineState {
+    const KIND: &'static str = "day25-machine";
+
+    fn to_record(&self) -> Record {
+        let mut record = Record::new(Self::KIND);
+        record.put("steps", self.steps);
+        record.put("steps_remaining", self.steps_remaining);
+        record.put("state", self.state);
+        record.put("position", self.cur_position);
 
-    fn next(&mut self) -> Option<Self::Item> {
+        // The tape is written out as 0s and 1s from its leftmost 1 to its rightmost.
+        let (lo, hi) = self.tape.iter().map(position).minmax().into_option().unwrap_or((0, -1));
+        record.put("tape_start", lo);
+        record.put("tape", (lo..=hi).map(|pos| if self.tape.contains(slot(pos)) { '1' } else { '0' }).collect::<String>());
+        record
+    }
+
+    fn from_record(record: &Record) -> std::result::Result<MachineState, String> {
+        let start = record.get::<isize>("tape_start")?;
+        let mut tape = BitSet::new();
+        for (pos, c) in (start..).zip(record.get_str("tape")?.chars()) {
+            match c {
+                '1' => { tape.insert(slot(pos)); },
+                '0' => (),
+                _ => return Err(format!("invalid tape cell {c:?}"))
+            }
+        }
+
+        Ok(MachineState {
+            tape,
+            state: record.get("state")?,
+            steps_remaining: record.get("steps_remaining")?,
+            cur_position: record.get("position")?,
+            steps: record.get("steps")?
+        })
+    }
+}
+
+impl Simulation for Machine {
+    type Output = usize;
+    type Snapshot = MachineState;
+
+    fn step(&mut self) -> Option<usize> {
         if self.steps_remaining == 0 {
             return None;
         }
 
-        let rule = if self.tape.contains(&self.cur_position) {
+        let cell = slot(self.cur_position);
+        let rule = if self.tape.contains(cell) {
             &self.rules[&self.state].1
         }
         else {
             &self.rules[&self.state].0
         };
 
-        if rule.to_write == true {
-            self.tape.insert(self.cur_position);
-        }
-        else {
-            self.tape.remove(&self.cur_position);
-        }
+        self.tape.set(cell, rule.to_write);
 
         self.cur_position += rule.offset;
         self.state = rule.next_state;
         self.steps_remaining -= 1;
+        self.steps += 1;
 
         Some(self.tape.len())
     }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+
+    fn snapshot(&self) -> MachineState {
+        MachineState {
+            tape: self.tape.clone(),
+            state: self.state,
+            steps_remaining: self.steps_remaining,
+            cur_position: self.cur_position,
+            steps: self.steps
+        }
+    }
+
+    fn restore(&mut self, snapshot: MachineState) {
+        self.tape = snapshot.tape;
+        self.state = snapshot.state;
+        self.steps_remaining = snapshot.steps_remaining;
+        self.cur_position = snapshot.cur_position;
+        self.steps = snapshot.steps;
+    }
 }
 
 fn parse_input(input: &str) -> IResult<&str, Machine> {
@@ -60,10 +159,6 @@ fn parse_input(input: &str) -> IResult<&str, Machine> {
             ))(input)
     }
 
-    fn number(input: &str) -> IResult<&str, usize> {
-        map_res(digit1, |s: &str| s.parse::<usize>())(input)
-    }
-
     fn offset(input: &str) -> IResult<&str, isize> {
         alt((
             value(-1, tag("left")),
@@ -94,7 +189,7 @@ fn parse_input(input: &str) -> IResult<&str, Machine> {
         all_consuming(
             tuple((
                 delimited(tag("Begin in state "), anychar, pair(ch('.'), newline)),
-                delimited(tag("Perform a diagnostic checksum after "), number, pair(tag(" steps."), multispace1)),
+                delimited(tag("Perform a diagnostic checksum after "), unsigned, pair(tag(" steps."), multispace1)),
                 separated_list1(multispace1, item)
             ))
         ),
@@ -103,14 +198,69 @@ fn parse_input(input: &str) -> IResult<&str, Machine> {
                 .map(|(a, r1, r2)| (a, (r1, r2)))
                 .collect();
 
-            Machine { rules, tape: HashSet::new(), state, steps_remaining, cur_position: 0 }
+            Machine { rules, tape: BitSet::new(), state, steps_remaining, cur_position: 0, steps: 0 }
         }
     )(input)
 }
 
 fn part1(input: &str) -> usize {
     let mut machine = parse_input(input).unwrap().1;
-    machine.last().unwrap()
+    machine.run().unwrap()
+}
+
+/// Like `part1`, but saving progress in `dir` every million steps and resuming from there if
+/// interrupted.
+fn checkpoint_part1(input: &str, dir: &Path) -> std::result::Result<Vec<(&'static str, String)>, String> {
+    let mut machine = parse_input(input).map_err(|e| e.to_string())?.1;
+    let path = path_for(dir, "day25-part1", input);
+
+    let steps = machine.steps_remaining;
+    run_checkpointed(&mut machine, steps, &path, 1000000).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(vec![("Part 1", machine.tape.len().to_string())])
+}
+
+fn animate_tape(input: &str, dir: &Path, _args: &[&str]) -> io::Result<()> {
+    const MAX_FRAMES: usize = 500;
+
+    // A first run finds how much of the tape the head ever reaches.
+    let mut machine = parse_input(input).unwrap().1;
+    let (mut lo, mut hi) = (0, 0);
+    while machine.step().is_some() {
+        lo = lo.min(machine.cur_position);
+        hi = hi.max(machine.cur_position);
+    }
+
+    let mut machine = parse_input(input).unwrap().1;
+    let mut recorder = Recorder::new(dir, "day25-tape")
+        .every(machine.steps_remaining.div_ceil(MAX_FRAMES).max(1))
+        .max_frames(MAX_FRAMES)
+        .scale(4);
+
+    let frame = |machine: &Machine| {
+        let cells = (lo..=hi)
+            .map(|pos| match (pos == machine.cur_position, machine.tape.contains(slot(pos))) {
+                (true, _) => Rgb::RED,
+                (false, true) => Rgb::WHITE,
+                (false, false) => Rgb::BLACK
+            })
+            .collect();
+
+        Grid::from_vec((hi - lo + 1) as usize, 1, cells)
+    };
+
+    loop {
+        recorder.step(|| frame(&machine), |&c| c)?;
+        if machine.step().is_none() {
+            break;
+        }
+    }
+
+    recorder.finish().map(drop)
 }
 
-build_main!("day25.txt", "Part 1" => part1);
\ No newline at end of file
+build_main!(
+    "day25.txt",
+    "Part 1" => { "default" => part1 };
+    render "tape" => animate_tape;
+    checkpoint checkpoint_part1
+);
\ No newline at end of file
@@ -0,0 +1,378 @@
+use std::hash::{Hash, Hasher};
+use crate::geom::Point2;
+use crate::grid::Grid;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A set of small non-negative integers, one bit each, growing as larger members are added.
+#[derive(Clone, Debug, Default)]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize
+}
+
+impl BitSet {
+    pub fn new() -> BitSet {
+        BitSet::default()
+    }
+
+    /// An empty set with room for `0..bits` before it has to grow.
+    pub fn with_capacity(bits: usize) -> BitSet {
+        BitSet { words: vec![0; bits.div_ceil(BITS)], len: 0 }
+    }
+
+    /// The number of members, kept up to date on every change rather than recounted.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.words.get(i / BITS).is_some_and(|&w| w & (1 << (i % BITS)) != 0)
+    }
+
+    /// Adds `i`, returning whether it was newly added.
+    pub fn insert(&mut self, i: usize) -> bool {
+        if i / BITS >= self.words.len() {
+            self.words.resize(i / BITS + 1, 0);
+        }
+
+        let word = &mut self.words[i / BITS];
+        let added = *word & (1 << (i % BITS)) == 0;
+        *word |= 1 << (i % BITS);
+        self.len += added as usize;
+        added
+    }
+
+    /// Removes `i`, returning whether it was present.
+    pub fn remove(&mut self, i: usize) -> bool {
+        let present = self.contains(i);
+        if present {
+            self.words[i / BITS] &= !(1 << (i % BITS));
+            self.len -= 1;
+        }
+        present
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        if value {
+            self.insert(i);
+        }
+        else {
+            self.remove(i);
+        }
+    }
+
+    /// Flips membership of `i`, returning whether it is now present.
+    pub fn toggle(&mut self, i: usize) -> bool {
+        if self.remove(i) {
+            false
+        }
+        else {
+            self.insert(i)
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+        self.len = 0;
+    }
+
+    /// The members in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        ones(&self.words)
+    }
+
+    /// The words up to the last non-zero one, so that equal sets compare and hash equally
+    /// whatever their capacity.
+    fn significant_words(&self) -> &[u64] {
+        let end = self.words.iter().rposition(|&w| w != 0).map_or(0, |i| i + 1);
+        &self.words[..end]
+    }
+}
+
+impl PartialEq for BitSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.significant_words() == other.significant_words()
+    }
+}
+
+impl Eq for BitSet {}
+
+impl Hash for BitSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.significant_words().hash(state);
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut result = BitSet::new();
+        iter.into_iter().for_each(|i| { result.insert(i); });
+        result
+    }
+}
+
+/// The indices of the set bits of `words`, least significant bit first.
+fn ones(words: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    words.iter().enumerate().flat_map(|(k, &word)| {
+        let mut rest = word;
+        std::iter::from_fn(move || {
+            (rest != 0).then(|| {
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                k * BITS + bit
+            })
+        })
+    })
+}
+
+/// A fixed-size grid of bits, stored row-major with each row starting on a fresh word.
+/// Positions are `Point2(row, col)`, as in `Grid`. Bits past the end of a row are always
+/// clear, so equal grids have equal words and the derived `Hash` can be used directly.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    stride: usize,
+    words: Vec<u64>
+}
+
+impl BitGrid {
+    /// An all-clear grid.
+    pub fn new(width: usize, height: usize) -> BitGrid {
+        let stride = width.div_ceil(BITS);
+        BitGrid { width, height, stride, words: vec![0; stride * height] }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Point2) -> bool) -> BitGrid {
+        let mut result = BitGrid::new(width, height);
+        for i in 0..height {
+            for j in 0..width {
+                let pos = Point2(i as isize, j as isize);
+                if f(pos) {
+                    result.set(pos, true);
+                }
+            }
+        }
+        result
+    }
+
+    /// Builds a grid from equal-length rows.
+    pub fn from_rows(rows: Vec<Vec<bool>>) -> BitGrid {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(rows.iter().all(|row| row.len() == width), "grid rows have different lengths");
+
+        BitGrid::from_fn(width, height, |Point2(i, j)| rows[i as usize][j as usize])
+    }
+
+    /// Parses one row per line, setting the bits whose characters satisfy `f`.
+    pub fn from_str(input: &str, mut f: impl FnMut(char) -> bool) -> BitGrid {
+        BitGrid::from(&Grid::from_str(input, &mut f))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, Point2(i, j): Point2) -> bool {
+        i >= 0 && j >= 0 && (i as usize) < self.height && (j as usize) < self.width
+    }
+
+    fn locate(&self, Point2(i, j): Point2) -> (usize, u64) {
+        let (i, j) = (i as usize, j as usize);
+        (i * self.stride + j / BITS, 1 << (j % BITS))
+    }
+
+    /// Whether the bit at `pos` is set; positions outside the grid read as clear.
+    pub fn get(&self, pos: Point2) -> bool {
+        if !self.in_bounds(pos) {
+            return false;
+        }
+
+        let (k, mask) = self.locate(pos);
+        self.words[k] & mask != 0
+    }
+
+    pub fn set(&mut self, pos: Point2, value: bool) {
+        assert!(self.in_bounds(pos), "{pos:?} is outside the grid");
+
+        let (k, mask) = self.locate(pos);
+        if value {
+            self.words[k] |= mask;
+        }
+        else {
+            self.words[k] &= !mask;
+        }
+    }
+
+    /// Flips the bit at `pos`, returning its new value.
+    pub fn toggle(&mut self, pos: Point2) -> bool {
+        assert!(self.in_bounds(pos), "{pos:?} is outside the grid");
+
+        let (k, mask) = self.locate(pos);
+        self.words[k] ^= mask;
+        self.words[k] & mask != 0
+    }
+
+    /// The number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// The words of row `i`, least significant bit first; bits past the width are clear.
+    pub fn row_words(&self, i: usize) -> &[u64] {
+        &self.words[i * self.stride..(i + 1) * self.stride]
+    }
+
+    pub fn row(&self, i: usize) -> impl Iterator<Item = bool> + '_ {
+        let words = self.row_words(i);
+        (0..self.width).map(move |j| words[j / BITS] & (1 << (j % BITS)) != 0)
+    }
+
+    /// The positions of the set bits, in row-major order.
+    pub fn ones(&self) -> impl Iterator<Item = Point2> + '_ {
+        (0..self.height).flat_map(move |i| {
+            ones(self.row_words(i)).map(move |j| Point2(i as isize, j as isize))
+        })
+    }
+
+    /// Copies out the `height` by `width` block whose top-left corner is `(top, left)`.
+    pub fn subgrid(&self, top: usize, left: usize, height: usize, width: usize) -> BitGrid {
+        assert!(left + width <= self.width && top + height <= self.height,
+                "a {height}x{width} block at ({top}, {left}) overhangs a {}x{} grid", self.height, self.width);
+
+        let offset = Point2(top as isize, left as isize);
+        BitGrid::from_fn(width, height, |pos| self.get(pos + offset))
+    }
+
+    /// Overwrites the block starting at `(top, left)` with the contents of `other`.
+    pub fn blit(&mut self, other: &BitGrid, top: usize, left: usize) {
+        assert!(left + other.width <= self.width && top + other.height <= self.height,
+                "a {}x{} block at ({top}, {left}) overhangs a {}x{} grid",
+                other.height, other.width, self.height, self.width);
+
+        let offset = Point2(top as isize, left as isize);
+        for i in 0..other.height {
+            for j in 0..other.width {
+                let pos = Point2(i as isize, j as isize);
+                self.set(pos + offset, other.get(pos));
+            }
+        }
+    }
+
+    fn last(&self) -> (isize, isize) {
+        (self.height as isize - 1, self.width as isize - 1)
+    }
+
+    /// Mirrors the grid left-to-right.
+    pub fn flip_horizontal(&self) -> BitGrid {
+        let (_, w) = self.last();
+        BitGrid::from_fn(self.width, self.height, |Point2(i, j)| self.get(Point2(i, w - j)))
+    }
+
+    /// Mirrors the grid top-to-bottom.
+    pub fn flip_vertical(&self) -> BitGrid {
+        let (h, _) = self.last();
+        BitGrid::from_fn(self.width, self.height, |Point2(i, j)| self.get(Point2(h - i, j)))
+    }
+
+    /// Mirrors a square grid in its main diagonal.
+    pub fn transpose(&self) -> BitGrid {
+        self.assert_square();
+        BitGrid::from_fn(self.width, self.height, |Point2(i, j)| self.get(Point2(j, i)))
+    }
+
+    /// Rotates a square grid a quarter turn clockwise.
+    pub fn rotate_right(&self) -> BitGrid {
+        self.assert_square();
+        let (h, _) = self.last();
+        BitGrid::from_fn(self.width, self.height, |Point2(i, j)| self.get(Point2(h - j, i)))
+    }
+
+    /// Rotates a square grid a quarter turn anticlockwise.
+    pub fn rotate_left(&self) -> BitGrid {
+        self.assert_square();
+        let (_, w) = self.last();
+        BitGrid::from_fn(self.width, self.height, |Point2(i, j)| self.get(Point2(j, w - i)))
+    }
+
+    fn assert_square(&self) {
+        assert_eq!(self.width, self.height, "only square grids can be rotated or transposed");
+    }
+
+    /// Renders one character per cell, one line per row.
+    pub fn render(&self, set: char, clear: char) -> String {
+        (0..self.height)
+            .map(|i| self.row(i).map(|b| if b { set } else { clear }).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl From<&Grid<bool>> for BitGrid {
+    fn from(grid: &Grid<bool>) -> BitGrid {
+        BitGrid::from_fn(grid.width(), grid.height(), |pos| grid[pos])
+    }
+}
+
+impl From<&BitGrid> for Grid<bool> {
+    fn from(grid: &BitGrid) -> Grid<bool> {
+        let cells = (0..grid.height).flat_map(|i| grid.row(i)).collect();
+        Grid::from_vec(grid.width, grid.height, cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitset() {
+        let mut set = BitSet::new();
+        assert!(set.insert(3));
+        assert!(!set.insert(3));
+        assert!(set.insert(200));
+        assert!(!set.toggle(3));
+        assert!(set.toggle(64));
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![64, 200]);
+
+        assert!(set.remove(200));
+        assert!(!set.remove(1000));
+        assert_eq!(set, [64].into_iter().collect());
+        assert_ne!(set, BitSet::with_capacity(1000));
+    }
+
+    #[test]
+    fn test_bitgrid() {
+        let grid = BitGrid::from_str("#..\n##.\n...", |c| c == '#');
+        assert_eq!(grid.count_ones(), 3);
+        assert!(grid.get(Point2(1, 1)));
+        assert!(!grid.get(Point2(-1, 0)));
+        assert_eq!(grid.ones().collect::<Vec<_>>(), vec![Point2(0, 0), Point2(1, 0), Point2(1, 1)]);
+
+        assert_eq!(grid.rotate_right().render('#', '.'), ".##\n.#.\n...");
+        assert_eq!(grid.rotate_left(), grid.rotate_right().rotate_right().rotate_right());
+        assert_eq!(grid.flip_horizontal().render('#', '.'), "..#\n.##\n...");
+        assert_eq!(grid.flip_vertical(), grid.rotate_right().rotate_right().flip_horizontal());
+        assert_eq!(grid.transpose(), grid.rotate_right().flip_horizontal());
+
+        let as_grid = Grid::from(&grid);
+        assert_eq!(BitGrid::from(&as_grid.rotate_right()), grid.rotate_right());
+
+        let mut wide = BitGrid::new(130, 2);
+        wide.blit(&grid.subgrid(1, 0, 1, 2), 1, 127);
+        assert_eq!(wide.row_words(1), &[0, 1 << 63, 1]);
+        assert!(wide.toggle(Point2(0, 129)));
+        assert_eq!(wide.count_ones(), 3);
+    }
+}
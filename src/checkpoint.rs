@@ -0,0 +1,285 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Display;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use crate::geom::Point2;
+use crate::grid::Grid;
+use crate::render::save;
+use crate::simulation::Simulation;
+
+/// The format version written into, and required of, every checkpoint header.
+pub const VERSION: u32 = 1;
+
+/// The contents of a checkpoint file: a kind, naming what was saved, and an ordered list of
+/// `key value` lines, where a key may repeat. On disk it reads
+///
+/// ```text
+/// checkpoint 1 day22-board1
+/// steps 10000
+/// row ..#.
+/// row .#..
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Record {
+    kind: String,
+    fields: Vec<(String, String)>
+}
+
+impl Record {
+    pub fn new(kind: &str) -> Record {
+        Record { kind: kind.to_string(), fields: Vec::new() }
+    }
+
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Appends a field; `value` must not contain a line break.
+    pub fn put(&mut self, key: &str, value: impl Display) {
+        let value = value.to_string();
+        assert!(!key.contains(char::is_whitespace), "checkpoint key {key:?} contains whitespace");
+        assert!(!value.contains('\n'), "checkpoint value for {key:?} spans several lines");
+        self.fields.push((key.to_string(), value));
+    }
+
+    /// The first value stored under `key`.
+    pub fn get_str(&self, key: &str) -> Result<&str, String> {
+        self.get_all(key).next().ok_or_else(|| format!("checkpoint has no {key:?}"))
+    }
+
+    /// Every value stored under `key`, in order.
+    pub fn get_all(&self, key: &str) -> impl Iterator<Item = &str> {
+        let key = key.to_string();
+        self.fields.iter().filter(move |(k, _)| *k == key).map(|(_, v)| v.as_str())
+    }
+
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<T, String> {
+        let value = self.get_str(key)?;
+        value.parse().map_err(|_| format!("invalid checkpoint {key:?}: {value:?}"))
+    }
+
+    pub fn put_point(&mut self, key: &str, Point2(i, j): Point2) {
+        self.put(key, format!("{i} {j}"));
+    }
+
+    pub fn get_point(&self, key: &str) -> Result<Point2, String> {
+        let value = self.get_str(key)?;
+        match value.split(' ').map(|n| n.parse::<isize>()).collect::<Vec<_>>().as_slice() {
+            [Ok(i), Ok(j)] => Ok(Point2(*i, *j)),
+            _ => Err(format!("invalid checkpoint {key:?}: {value:?}"))
+        }
+    }
+
+    /// Stores `grid` as one `key` line per row, with a character per cell.
+    pub fn put_grid<T>(&mut self, key: &str, grid: &Grid<T>, f: impl Fn(&T) -> char) {
+        for row in grid.rows() {
+            self.put(key, row.iter().map(&f).collect::<String>());
+        }
+    }
+
+    pub fn get_grid<T>(&self, key: &str, f: impl Fn(char) -> Option<T>) -> Result<Grid<T>, String> {
+        let rows = self.get_all(key)
+            .map(|row| {
+                row.chars()
+                    .map(|c| f(c).ok_or_else(|| format!("invalid checkpoint {key:?} cell {c:?}")))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if rows.iter().any(|row| row.len() != rows[0].len()) {
+            return Err(format!("checkpoint {key:?} rows have different lengths"));
+        }
+
+        Ok(Grid::from_rows(rows))
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "checkpoint {VERSION} {}", self.kind)?;
+        for (key, value) in self.fields.iter() {
+            writeln!(out, "{key} {value}")?;
+        }
+        Ok(())
+    }
+
+    pub fn parse(input: &str) -> Result<Record, String> {
+        let mut lines = input.lines();
+
+        let header = lines.next().unwrap_or("");
+        let kind = match header.split(' ').collect::<Vec<_>>().as_slice() {
+            ["checkpoint", version, kind] if *version == VERSION.to_string() => kind.to_string(),
+            ["checkpoint", version, _] => return Err(format!("unsupported checkpoint version {version}")),
+            _ => return Err(format!("not a checkpoint: {header:?}"))
+        };
+
+        let fields = lines
+            .map(|line| {
+                let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+                (key.to_string(), value.to_string())
+            })
+            .collect();
+
+        Ok(Record { kind, fields })
+    }
+}
+
+/// State that can be written to a checkpoint file and read back.
+pub trait Checkpoint: Sized {
+    /// Written into the header, so that a file cannot be loaded as the wrong kind of state.
+    const KIND: &'static str;
+
+    fn to_record(&self) -> Record;
+
+    fn from_record(record: &Record) -> Result<Self, String>;
+
+    /// Writes the checkpoint next to `path` and then moves it into place, so an interrupted
+    /// save leaves any earlier checkpoint intact.
+    fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let partial = path.with_extension("partial");
+
+        save(&partial, |out| self.to_record().write(out))?;
+        fs::rename(partial, path)
+    }
+
+    fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+
+        let record = Record::parse(&fs::read_to_string(path)?).map_err(invalid)?;
+        if record.kind() != Self::KIND {
+            return Err(invalid(format!("expected a {} checkpoint, found {}", Self::KIND, record.kind())));
+        }
+
+        Self::from_record(&record).map_err(invalid)
+    }
+}
+
+/// A checkpoint file in `dir` for the run called `name` on this particular `input`, so that
+/// a checkpoint is never resumed against different input.
+pub fn path_for(dir: impl AsRef<Path>, name: &str, input: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    input.hash(&mut hasher);
+    dir.as_ref().join(format!("{name}-{:016x}.txt", hasher.finish()))
+}
+
+/// Runs `sim` until it has taken `steps` steps or finished, saving a checkpoint to `path`
+/// every `every` steps. A run starts from the checkpoint at `path` if there is one, and
+/// removes it once complete.
+pub fn run_checkpointed<S>(sim: &mut S, steps: usize, path: &Path, every: usize) -> io::Result<()>
+where S: Simulation, S::Snapshot: Checkpoint
+{
+    assert!(every > 0, "checkpoints must be at least one step apart");
+
+    if path.exists() {
+        sim.restore(S::Snapshot::load(path)?);
+    }
+
+    while sim.steps() < steps {
+        let before = sim.steps();
+        sim.run_for(every.min(steps - before));
+        if sim.steps() == before {
+            break;
+        }
+
+        sim.snapshot().save(path)?;
+    }
+
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut record = Record::new("test");
+        record.put("steps", 42);
+        record.put_point("pos", Point2(-3, 7));
+        record.put_grid("row", &Grid::from_str("#.\n.#", |c| c == '#'), |&b| if b { '#' } else { '.' });
+        record.put("empty", "");
+
+        let mut out = Vec::new();
+        record.write(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("checkpoint 1 test\nsteps 42\npos -3 7\nrow #.\n"));
+
+        let parsed = Record::parse(&text).unwrap();
+        assert_eq!(parsed, record);
+        assert_eq!(parsed.get::<usize>("steps"), Ok(42));
+        assert_eq!(parsed.get_point("pos"), Ok(Point2(-3, 7)));
+        assert_eq!(parsed.get_grid("row", |c| Some(c == '#')).unwrap().count(|&b| b), 2);
+        assert_eq!(parsed.get_str("empty"), Ok(""));
+        assert!(parsed.get::<usize>("pos").is_err());
+        assert!(parsed.get_str("missing").is_err());
+
+        assert!(Record::parse("checkpoint 2 test\n").unwrap_err().contains("version"));
+        assert!(Record::parse("steps 42\n").is_err());
+    }
+
+    /// Counts upwards for ever.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Counter(usize);
+
+    impl Simulation for Counter {
+        type Output = usize;
+        type Snapshot = Counter;
+
+        fn step(&mut self) -> Option<usize> {
+            self.0 += 1;
+            Some(self.0)
+        }
+
+        fn steps(&self) -> usize {
+            self.0
+        }
+
+        fn snapshot(&self) -> Counter {
+            self.clone()
+        }
+
+        fn restore(&mut self, snapshot: Counter) {
+            *self = snapshot;
+        }
+    }
+
+    impl Checkpoint for Counter {
+        const KIND: &'static str = "counter";
+
+        fn to_record(&self) -> Record {
+            let mut record = Record::new(Self::KIND);
+            record.put("steps", self.0);
+            record
+        }
+
+        fn from_record(record: &Record) -> Result<Counter, String> {
+            Ok(Counter(record.get("steps")?))
+        }
+    }
+
+    #[test]
+    fn test_run_checkpointed() {
+        let dir = std::env::temp_dir().join(format!("checkpoint-test-{}", std::process::id()));
+        let path = path_for(&dir, "counter", "input");
+        assert_ne!(path, path_for(&dir, "counter", "other input"));
+
+        Counter(7).save(&path).unwrap();
+        assert_eq!(Counter::load(&path).unwrap(), Counter(7));
+
+        let mut counter = Counter(0);
+        run_checkpointed(&mut counter, 20, &path, 5).unwrap();
+        assert_eq!(counter, Counter(20));
+        assert!(!path.exists());
+
+        fs::write(&path, "checkpoint 1 board\nsteps 3\n").unwrap();
+        assert_eq!(Counter::load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
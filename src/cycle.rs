@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The shape of the sequence `x0, f(x0), f(f(x0)), ...`: the first `start` states are never
+/// revisited, after which the sequence repeats every `length` steps.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize
+}
+
+impl Cycle {
+    /// The smallest step count that reaches the same state as `n` steps do.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        }
+        else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// Floyd's tortoise and hare: constant memory, about three applications of `f` per step.
+pub fn floyd<S: Clone + PartialEq>(initial: &S, f: impl Fn(&S) -> S) -> Cycle {
+    let mut tortoise = f(initial);
+    let mut hare = f(&tortoise);
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&f(&hare));
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = f(&tortoise);
+    while tortoise != hare {
+        hare = f(&hare);
+        length += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Brent's algorithm: constant memory, and usually fewer applications of `f` than Floyd's.
+pub fn brent<S: Clone + PartialEq>(initial: &S, f: impl Fn(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = f(initial);
+
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = f(&hare);
+        length += 1;
+    }
+
+    tortoise = initial.clone();
+    hare = initial.clone();
+    for _ in 0..length {
+        hare = f(&hare);
+    }
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Remembers every state seen: applies `f` only `start + length + 1` times, at the cost of
+/// storing `start + length` states.
+pub fn hashed<S: Clone + Hash + Eq>(initial: &S, f: impl Fn(&S) -> S) -> Cycle {
+    let mut seen = HashMap::new();
+    let mut cur = initial.clone();
+
+    for i in 0.. {
+        let next = f(&cur);
+        if let Some(start) = seen.insert(cur, i) {
+            return Cycle { start, length: i - start };
+        }
+        cur = next;
+    }
+
+    unreachable!()
+}
+
+/// The state after `n` applications of `f`, skipping whole trips around the cycle.
+pub fn state_after<S: Clone + PartialEq>(initial: &S, f: impl Fn(&S) -> S, n: usize) -> S {
+    let cycle = brent(initial, &f);
+    (0..cycle.reduce(n)).fold(initial.clone(), |cur, _| f(&cur))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle() {
+        // 3 -> 10 -> 5 -> 16 -> 8 -> 4 -> 2 -> 1 -> 4 -> ...
+        let collatz = |&n: &u64| if n % 2 == 0 { n / 2 } else { 3 * n + 1 };
+        let expected = Cycle { start: 5, length: 3 };
+
+        assert_eq!(floyd(&3, collatz), expected);
+        assert_eq!(brent(&3, collatz), expected);
+        assert_eq!(hashed(&3, collatz), expected);
+        assert_eq!(floyd(&4, collatz), Cycle { start: 0, length: 3 });
+        assert_eq!(brent(&4, collatz), Cycle { start: 0, length: 3 });
+
+        assert_eq!(expected.reduce(4), 4);
+        assert_eq!(expected.reduce(1_000_000_000), 5 + (1_000_000_000 - 5) % 3);
+        assert_eq!(state_after(&3, collatz, 1_000_000_000), 1);
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Union-find over the dense ids `0..len`, with path compression and union by size.
+#[derive(Clone, Debug)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    count: usize
+}
+
+impl DisjointSet {
+    /// `n` singleton sets.
+    pub fn new(n: usize) -> DisjointSet {
+        DisjointSet { parent: (0..n).collect(), size: vec![1; n], count: n }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Adds a new singleton set, returning its id.
+    pub fn add(&mut self) -> usize {
+        let id = self.parent.len();
+        self.parent.push(id);
+        self.size.push(1);
+        self.count += 1;
+        id
+    }
+
+    /// The representative of the set containing `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        let mut cur = x;
+        while self.parent[cur] != root {
+            let next = self.parent[cur];
+            self.parent[cur] = root;
+            cur = next;
+        }
+
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`, returning false if they were already one set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        if self.size[a] < self.size[b] {
+            (a, b) = (b, a);
+        }
+
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.count -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The size of the set containing `x`.
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// The number of disjoint sets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Every set, each listed in increasing id order, ordered by smallest member.
+    pub fn groups(&mut self) -> impl Iterator<Item = Vec<usize>> {
+        let mut group_of = HashMap::new();
+        let mut groups: Vec<Vec<usize>> = Vec::with_capacity(self.count);
+
+        for x in 0..self.len() {
+            let root = self.find(x);
+            let k = *group_of.entry(root).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[k].push(x);
+        }
+
+        groups.into_iter()
+    }
+}
+
+/// A `DisjointSet` over arbitrary keys, which are added on first use.
+#[derive(Clone, Debug)]
+pub struct KeyedDisjointSet<K> {
+    sets: DisjointSet,
+    index: HashMap<K, usize>,
+    keys: Vec<K>
+}
+
+impl<K: Hash + Eq + Clone> Default for KeyedDisjointSet<K> {
+    fn default() -> Self {
+        KeyedDisjointSet::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone> KeyedDisjointSet<K> {
+    pub fn new() -> KeyedDisjointSet<K> {
+        KeyedDisjointSet { sets: DisjointSet::new(0), index: HashMap::new(), keys: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Adds `key` as a singleton if it is new, returning its dense id either way.
+    pub fn insert(&mut self, key: K) -> usize {
+        if let Some(&id) = self.index.get(&key) {
+            return id;
+        }
+
+        let id = self.sets.add();
+        self.index.insert(key.clone(), id);
+        self.keys.push(key);
+        id
+    }
+
+    pub fn union(&mut self, a: K, b: K) -> bool {
+        let (a, b) = (self.insert(a), self.insert(b));
+        self.sets.union(a, b)
+    }
+
+    /// Whether `a` and `b` are in the same set; unknown keys are in no set.
+    pub fn same(&mut self, a: &K, b: &K) -> bool {
+        match (self.index.get(a), self.index.get(b)) {
+            (Some(&a), Some(&b)) => self.sets.same(a, b),
+            _ => false
+        }
+    }
+
+    pub fn size_of(&mut self, key: &K) -> Option<usize> {
+        let &id = self.index.get(key)?;
+        Some(self.sets.size_of(id))
+    }
+
+    pub fn count(&self) -> usize {
+        self.sets.count()
+    }
+
+    pub fn groups(&mut self) -> impl Iterator<Item = Vec<&K>> {
+        let keys = &self.keys;
+        self.sets.groups().map(move |group| group.into_iter().map(|id| &keys[id]).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disjoint_set() {
+        let mut sets = DisjointSet::new(6);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+
+        assert_eq!(sets.count(), 3);
+        assert_eq!(sets.size_of(3), 4);
+        assert!(sets.same(0, 3));
+        assert!(!sets.same(0, 4));
+        assert_eq!(sets.groups().collect::<Vec<_>>(), vec![vec![0, 1, 2, 3], vec![4], vec![5]]);
+    }
+
+    #[test]
+    fn test_keyed_disjoint_set() {
+        let mut sets = KeyedDisjointSet::new();
+        sets.union("a", "b");
+        sets.union("c", "d");
+        sets.insert("e");
+        sets.union("b", "a");
+
+        assert_eq!(sets.count(), 3);
+        assert_eq!(sets.size_of(&"a"), Some(2));
+        assert_eq!(sets.size_of(&"z"), None);
+        assert!(sets.same(&"a", &"b"));
+        assert!(!sets.same(&"a", &"c"));
+        assert_eq!(sets.groups().collect::<Vec<_>>(), vec![vec![&"a", &"b"], vec![&"c", &"d"], vec![&"e"]]);
+    }
+}
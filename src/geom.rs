@@ -0,0 +1,431 @@
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Signed integer coordinates: enough arithmetic for norms, rotations and neighbourhoods.
+pub trait Coord: Copy + Ord + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self> {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+}
+
+macro_rules! impl_coord {
+    ($($t:ty),+) => {
+        $(
+        impl Coord for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+        }
+        )+
+    };
+}
+
+impl_coord!(i8, i16, i32, i64, i128, isize);
+
+/// A point (or vector) in the plane. The days use it both as `(x, y)` and as `(row, col)`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Point2<T = isize>(pub T, pub T);
+
+impl<T: Coord> Point2<T> {
+    pub fn origin() -> Point2<T> {
+        Point2(T::ZERO, T::ZERO)
+    }
+
+    pub fn dot(&self, other: Point2<T>) -> T {
+        self.0 * other.0 + self.1 * other.1
+    }
+
+    pub fn manhattan(&self) -> T {
+        self.0.abs() + self.1.abs()
+    }
+
+    pub fn manhattan_distance(&self, other: Point2<T>) -> T {
+        (*self - other).manhattan()
+    }
+
+    pub fn chebyshev(&self) -> T {
+        self.0.abs().max(self.1.abs())
+    }
+
+    pub fn chebyshev_distance(&self, other: Point2<T>) -> T {
+        (*self - other).chebyshev()
+    }
+
+    /// Quarter turn `(a, b) -> (-b, a)`: anticlockwise for x-right/y-up axes, and equally a
+    /// left turn for `(row, col)` coordinates.
+    pub fn rotate_left(&self) -> Point2<T> {
+        Point2(-self.1, self.0)
+    }
+
+    pub fn rotate_right(&self) -> Point2<T> {
+        Point2(self.1, -self.0)
+    }
+
+    pub fn rotate_180(&self) -> Point2<T> {
+        Point2(-self.0, -self.1)
+    }
+
+    /// The four orthogonal neighbours, in rotation order starting from `+0`.
+    pub fn neighbors4(&self) -> [Point2<T>; 4] {
+        let (zero, one) = (T::ZERO, T::ONE);
+
+        [
+            *self + Point2(one, zero),
+            *self + Point2(zero, one),
+            *self + Point2(-one, zero),
+            *self + Point2(zero, -one)
+        ]
+    }
+
+    /// All eight surrounding points, in rotation order starting from `+0`.
+    pub fn neighbors8(&self) -> [Point2<T>; 8] {
+        let (zero, one) = (T::ZERO, T::ONE);
+
+        [
+            *self + Point2(one, zero),
+            *self + Point2(one, one),
+            *self + Point2(zero, one),
+            *self + Point2(-one, one),
+            *self + Point2(-one, zero),
+            *self + Point2(-one, -one),
+            *self + Point2(zero, -one),
+            *self + Point2(one, -one)
+        ]
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point2<T> {
+    type Output = Point2<T>;
+    fn add(self, rhs: Self) -> Self::Output {
+        Point2(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl<T: AddAssign> AddAssign for Point2<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+        self.1 += rhs.1;
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point2<T> {
+    type Output = Point2<T>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Point2(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl<T: SubAssign> SubAssign for Point2<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+        self.1 -= rhs.1;
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point2<T> {
+    type Output = Point2<T>;
+    fn neg(self) -> Self::Output {
+        Point2(-self.0, -self.1)
+    }
+}
+
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point2<T> {
+    type Output = Point2<T>;
+    fn mul(self, rhs: T) -> Self::Output {
+        Point2(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl<T: Copy + MulAssign> MulAssign<T> for Point2<T> {
+    fn mul_assign(&mut self, rhs: T) {
+        self.0 *= rhs;
+        self.1 *= rhs;
+    }
+}
+
+/// A point (or vector) in space.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Point3<T = isize>(pub T, pub T, pub T);
+
+impl<T: Coord> Point3<T> {
+    pub fn origin() -> Point3<T> {
+        Point3(T::ZERO, T::ZERO, T::ZERO)
+    }
+
+    pub fn dot(&self, other: Point3<T>) -> T {
+        self.0 * other.0 + self.1 * other.1 + self.2 * other.2
+    }
+
+    pub fn manhattan(&self) -> T {
+        self.0.abs() + self.1.abs() + self.2.abs()
+    }
+
+    pub fn manhattan_distance(&self, other: Point3<T>) -> T {
+        (*self - other).manhattan()
+    }
+
+    pub fn chebyshev(&self) -> T {
+        self.0.abs().max(self.1.abs()).max(self.2.abs())
+    }
+
+    pub fn chebyshev_distance(&self, other: Point3<T>) -> T {
+        (*self - other).chebyshev()
+    }
+
+    /// The six face-adjacent neighbours.
+    pub fn neighbors6(&self) -> [Point3<T>; 6] {
+        let (zero, one) = (T::ZERO, T::ONE);
+
+        [
+            *self + Point3(one, zero, zero),
+            *self + Point3(-one, zero, zero),
+            *self + Point3(zero, one, zero),
+            *self + Point3(zero, -one, zero),
+            *self + Point3(zero, zero, one),
+            *self + Point3(zero, zero, -one)
+        ]
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point3<T> {
+    type Output = Point3<T>;
+    fn add(self, rhs: Self) -> Self::Output {
+        Point3(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
+    }
+}
+
+impl<T: AddAssign> AddAssign for Point3<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+        self.1 += rhs.1;
+        self.2 += rhs.2;
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point3<T> {
+    type Output = Point3<T>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Point3(self.0 - rhs.0, self.1 - rhs.1, self.2 - rhs.2)
+    }
+}
+
+impl<T: SubAssign> SubAssign for Point3<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+        self.1 -= rhs.1;
+        self.2 -= rhs.2;
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point3<T> {
+    type Output = Point3<T>;
+    fn neg(self) -> Self::Output {
+        Point3(-self.0, -self.1, -self.2)
+    }
+}
+
+impl<T: Copy + Mul<Output = T>> Mul<T> for Point3<T> {
+    type Output = Point3<T>;
+    fn mul(self, rhs: T) -> Self::Output {
+        Point3(self.0 * rhs, self.1 * rhs, self.2 * rhs)
+    }
+}
+
+impl<T: Copy + MulAssign> MulAssign<T> for Point3<T> {
+    fn mul_assign(&mut self, rhs: T) {
+        self.0 *= rhs;
+        self.1 *= rhs;
+        self.2 *= rhs;
+    }
+}
+
+/// A compass heading. Unit vectors are in `(row, col)` coordinates, with rows growing downwards.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Direction { North, East, South, West }
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+    pub fn turn_left(self) -> Direction {
+        Direction::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn turn_right(self) -> Direction {
+        Direction::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn reverse(self) -> Direction {
+        Direction::ALL[(self as usize + 2) % 4]
+    }
+
+    /// The unit step in `(row, col)` coordinates.
+    pub fn to_point(self) -> Point2 {
+        match self {
+            Direction::North => Point2(-1, 0),
+            Direction::East => Point2(0, 1),
+            Direction::South => Point2(1, 0),
+            Direction::West => Point2(0, -1)
+        }
+    }
+
+    /// The unit step in `(x, y)` coordinates, with y growing upwards.
+    pub fn to_xy(self) -> Point2 {
+        match self {
+            Direction::North => Point2(0, 1),
+            Direction::East => Point2(1, 0),
+            Direction::South => Point2(0, -1),
+            Direction::West => Point2(-1, 0)
+        }
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = String;
+
+    /// Accepts compass letters (`n`, `e`, `s`, `w`) and arrow letters (`U`, `R`, `D`, `L`) in
+    /// either case.
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c.to_ascii_uppercase() {
+            'N' | 'U' => Ok(Direction::North),
+            'E' | 'R' => Ok(Direction::East),
+            'S' | 'D' => Ok(Direction::South),
+            'W' | 'L' => Ok(Direction::West),
+            _ => Err(format!("invalid direction {c:?}"))
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Direction::try_from(c),
+            _ => Err(format!("invalid direction {s:?}"))
+        }
+    }
+}
+
+/// Writes the compass letter, which `FromStr` reads back.
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Direction::North => 'N',
+            Direction::East => 'E',
+            Direction::South => 'S',
+            Direction::West => 'W'
+        };
+        write!(f, "{c}")
+    }
+}
+
+/// A position together with a heading.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Turtle {
+    pub pos: Point2,
+    pub heading: Direction
+}
+
+impl Turtle {
+    pub fn new(pos: Point2, heading: Direction) -> Turtle {
+        Turtle { pos, heading }
+    }
+
+    pub fn turn_left(&mut self) {
+        self.heading = self.heading.turn_left();
+    }
+
+    pub fn turn_right(&mut self) {
+        self.heading = self.heading.turn_right();
+    }
+
+    pub fn reverse(&mut self) {
+        self.heading = self.heading.reverse();
+    }
+
+    pub fn step(&mut self) {
+        self.pos += self.heading.to_point();
+    }
+
+    pub fn forward(&mut self, n: isize) {
+        self.pos += self.heading.to_point() * n;
+    }
+
+    /// The position one step ahead, without moving.
+    pub fn ahead(&self) -> Point2 {
+        self.pos + self.heading.to_point()
+    }
+
+    /// The position one step to the left, without moving or turning.
+    pub fn to_left(&self) -> Point2 {
+        self.pos + self.heading.turn_left().to_point()
+    }
+
+    /// The position one step to the right, without moving or turning.
+    pub fn to_right(&self) -> Point2 {
+        self.pos + self.heading.turn_right().to_point()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point2() {
+        let p = Point2(3, -4);
+
+        assert_eq!(p + Point2(1, 1), Point2(4, -3));
+        assert_eq!(p - Point2(1, 1), Point2(2, -5));
+        assert_eq!(p * 2, Point2(6, -8));
+        assert_eq!(p.manhattan(), 7);
+        assert_eq!(p.chebyshev(), 4);
+        assert_eq!(p.manhattan_distance(Point2(0, 0)), 7);
+
+        assert_eq!(p.rotate_left(), Point2(4, 3));
+        assert_eq!(p.rotate_right(), Point2(-4, -3));
+        assert_eq!(p.rotate_left().rotate_left(), p.rotate_180());
+        assert_eq!(p.rotate_left().rotate_right(), p);
+
+        assert!(Point2(0, 0).neighbors8().iter().all(|n| n.chebyshev() == 1));
+        assert!(Point2(0, 0).neighbors4().iter().all(|n| n.manhattan() == 1));
+    }
+
+    #[test]
+    fn test_point3() {
+        let p = Point3(1, -2, 3);
+
+        assert_eq!(p + p, p * 2);
+        assert_eq!(p - p, Point3::origin());
+        assert_eq!(p.manhattan(), 6);
+        assert_eq!(p.chebyshev(), 3);
+        assert!(p.neighbors6().iter().all(|&n| n.manhattan_distance(p) == 1));
+    }
+
+    #[test]
+    fn test_direction() {
+        use Direction::*;
+
+        assert_eq!(North.turn_left(), West);
+        assert_eq!(West.turn_right(), North);
+        assert_eq!(East.reverse(), West);
+        assert!(Direction::ALL.iter().all(|d| d.turn_left().to_point() == d.to_point().rotate_left()));
+        assert_eq!("u".parse::<Direction>(), Ok(North));
+        assert_eq!("L".parse::<Direction>(), Ok(West));
+        assert!("x".parse::<Direction>().is_err());
+        assert!(Direction::ALL.iter().all(|d| d.to_string().parse::<Direction>() == Ok(*d)));
+
+        let mut turtle = Turtle::new(Point2(0, 0), North);
+        turtle.step();
+        turtle.turn_right();
+        turtle.forward(3);
+        assert_eq!(turtle, Turtle::new(Point2(-1, 3), East));
+        assert_eq!(turtle.to_left(), Point2(-2, 3));
+    }
+}
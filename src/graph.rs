@@ -0,0 +1,291 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// A directed graph stored as adjacency lists. Nodes are kept in insertion order, which every
+/// traversal below respects, so results are deterministic.
+#[derive(Clone, Debug)]
+pub struct Graph<N> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    edges: Vec<Vec<usize>>
+}
+
+impl<N: Hash + Eq + Clone> Default for Graph<N> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+
+impl<N: Hash + Eq + Clone> Graph<N> {
+    pub fn new() -> Graph<N> {
+        Graph { nodes: Vec::new(), index: HashMap::new(), edges: Vec::new() }
+    }
+
+    /// Adds `node` if it is not already present, returning its id.
+    pub fn add_node(&mut self, node: N) -> usize {
+        if let Some(&id) = self.index.get(&node) {
+            return id;
+        }
+
+        let id = self.nodes.len();
+        self.index.insert(node.clone(), id);
+        self.nodes.push(node);
+        self.edges.push(Vec::new());
+        id
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.edges[from].push(to);
+    }
+
+    pub fn add_undirected_edge(&mut self, a: N, b: N) {
+        self.add_edge(a.clone(), b.clone());
+        self.add_edge(b, a);
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.index.contains_key(node)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.iter()
+    }
+
+    pub fn neighbors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.index.get(node).into_iter()
+            .flat_map(|&id| self.edges[id].iter().map(|&j| &self.nodes[j]))
+    }
+
+    /// Breadth-first traversal from `start`, yielding each reachable node with its distance.
+    pub fn bfs(&self, start: &N) -> Bfs<'_, N> {
+        let mut seen = vec![false; self.len()];
+        let mut queue = VecDeque::new();
+
+        if let Some(&id) = self.index.get(start) {
+            seen[id] = true;
+            queue.push_back((id, 0));
+        }
+
+        Bfs { graph: self, seen, queue }
+    }
+
+    /// Depth-first traversal from `start`, yielding nodes in preorder.
+    pub fn dfs(&self, start: &N) -> Dfs<'_, N> {
+        let seen = vec![false; self.len()];
+        let stack = self.index.get(start).into_iter().cloned().collect();
+
+        Dfs { graph: self, seen, stack }
+    }
+
+    /// The weakly connected components, i.e. ignoring edge direction.
+    pub fn components(&self) -> Components<N> {
+        let mut undirected = self.edges.clone();
+        for (i, out) in self.edges.iter().enumerate() {
+            out.iter().for_each(|&j| undirected[j].push(i));
+        }
+
+        let mut of = vec![usize::MAX; self.len()];
+        let mut groups = Vec::new();
+
+        for i in 0..self.len() {
+            if of[i] != usize::MAX {
+                continue;
+            }
+
+            let group_id = groups.len();
+            let mut group = vec![i];
+            let mut stack = vec![i];
+            of[i] = group_id;
+
+            while let Some(u) = stack.pop() {
+                for &v in undirected[u].iter() {
+                    if of[v] == usize::MAX {
+                        of[v] = group_id;
+                        group.push(v);
+                        stack.push(v);
+                    }
+                }
+            }
+
+            groups.push(group);
+        }
+
+        Components {
+            of: self.nodes.iter().cloned().zip(of).collect(),
+            groups: groups.into_iter()
+                .map(|group| group.into_iter().map(|i| self.nodes[i].clone()).collect())
+                .collect()
+        }
+    }
+
+    /// Orders the nodes so that every edge points forwards, or `None` if there is a cycle.
+    pub fn topological_sort(&self) -> Option<Vec<&N>> {
+        let mut in_degree = vec![0; self.len()];
+        self.edges.iter().flatten().for_each(|&j| in_degree[j] += 1);
+
+        let mut queue = (0..self.len()).filter(|&i| in_degree[i] == 0).collect::<VecDeque<_>>();
+        let mut result = Vec::with_capacity(self.len());
+
+        while let Some(i) = queue.pop_front() {
+            result.push(&self.nodes[i]);
+            for &j in self.edges[i].iter() {
+                in_degree[j] -= 1;
+                if in_degree[j] == 0 {
+                    queue.push_back(j);
+                }
+            }
+        }
+
+        (result.len() == self.len()).then_some(result)
+    }
+
+    /// A path with the fewest edges from `start` to `goal`, both included.
+    pub fn shortest_path(&self, start: &N, goal: &N) -> Option<Vec<&N>> {
+        let (&start, &goal) = (self.index.get(start)?, self.index.get(goal)?);
+        let mut pred = vec![usize::MAX; self.len()];
+        let mut queue = VecDeque::from([start]);
+        pred[start] = start;
+
+        while let Some(u) = queue.pop_front() {
+            if u == goal {
+                let mut path = vec![&self.nodes[goal]];
+                let mut cur = goal;
+                while cur != start {
+                    cur = pred[cur];
+                    path.push(&self.nodes[cur]);
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for &v in self.edges[u].iter() {
+                if pred[v] == usize::MAX {
+                    pred[v] = u;
+                    queue.push_back(v);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+pub struct Bfs<'a, N> {
+    graph: &'a Graph<N>,
+    seen: Vec<bool>,
+    queue: VecDeque<(usize, usize)>
+}
+
+impl<'a, N> Iterator for Bfs<'a, N> {
+    type Item = (&'a N, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (u, depth) = self.queue.pop_front()?;
+
+        for &v in self.graph.edges[u].iter() {
+            if !self.seen[v] {
+                self.seen[v] = true;
+                self.queue.push_back((v, depth + 1));
+            }
+        }
+
+        Some((&self.graph.nodes[u], depth))
+    }
+}
+
+pub struct Dfs<'a, N> {
+    graph: &'a Graph<N>,
+    seen: Vec<bool>,
+    stack: Vec<usize>
+}
+
+impl<'a, N> Iterator for Dfs<'a, N> {
+    type Item = &'a N;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(u) = self.stack.pop() {
+            if self.seen[u] {
+                continue;
+            }
+
+            self.seen[u] = true;
+            self.stack.extend(self.graph.edges[u].iter().rev().filter(|&&v| !self.seen[v]));
+            return Some(&self.graph.nodes[u]);
+        }
+
+        None
+    }
+}
+
+/// The result of `Graph::components`: every group, plus which group each node belongs to.
+#[derive(Clone, Debug)]
+pub struct Components<N> {
+    of: HashMap<N, usize>,
+    groups: Vec<Vec<N>>
+}
+
+impl<N: Hash + Eq> Components<N> {
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn component_of(&self, node: &N) -> Option<usize> {
+        self.of.get(node).copied()
+    }
+
+    pub fn members(&self, component: usize) -> &[N] {
+        &self.groups[component]
+    }
+
+    pub fn connected(&self, a: &N, b: &N) -> bool {
+        matches!((self.component_of(a), self.component_of(b)), (Some(x), Some(y)) if x == y)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[N]> {
+        self.groups.iter().map(|group| group.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph() {
+        let mut graph = Graph::new();
+        [("a", "b"), ("b", "c"), ("a", "d"), ("d", "c"), ("e", "f")].into_iter()
+            .for_each(|(u, v)| graph.add_edge(u, v));
+
+        assert_eq!(graph.bfs(&"a").map(|(n, d)| (*n, d)).collect::<Vec<_>>(),
+                   vec![("a", 0), ("b", 1), ("d", 1), ("c", 2)]);
+        assert_eq!(graph.dfs(&"a").cloned().collect::<Vec<_>>(), vec!["a", "b", "c", "d"]);
+        assert_eq!(graph.shortest_path(&"a", &"c"), Some(vec![&"a", &"b", &"c"]));
+        assert_eq!(graph.shortest_path(&"c", &"a"), None);
+
+        let components = graph.components();
+        assert_eq!(components.len(), 2);
+        assert!(components.connected(&"c", &"a"));
+        assert!(!components.connected(&"a", &"e"));
+
+        let order = graph.topological_sort().unwrap();
+        let pos = |n: &str| order.iter().position(|&&m| m == n).unwrap();
+        assert!(pos("a") < pos("b") && pos("b") < pos("c") && pos("d") < pos("c"));
+
+        graph.add_edge("c", "a");
+        assert_eq!(graph.topological_sort(), None);
+    }
+}
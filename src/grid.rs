@@ -0,0 +1,418 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+use crate::geom::Point2;
+
+/// A dense rectangular grid, stored row-major. Positions are `Point2(row, col)`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where T: Clone
+    {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(cells.len(), width * height, "grid data does not match its dimensions");
+        Grid { width, height, cells }
+    }
+
+    /// Builds a grid from equal-length rows.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Grid<T> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(rows.iter().all(|row| row.len() == width), "grid rows have different lengths");
+
+        Grid { width, height, cells: rows.into_iter().flatten().collect() }
+    }
+
+    /// Parses one row per line, mapping each character with `f`. Short lines are padded as if
+    /// they ended in spaces.
+    pub fn from_str(input: &str, mut f: impl FnMut(char) -> T) -> Grid<T> {
+        let lines = input.lines().collect::<Vec<_>>();
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let mut cells = Vec::with_capacity(width * lines.len());
+
+        for line in lines.iter() {
+            let mut len = 0;
+            for c in line.chars() {
+                cells.push(f(c));
+                len += 1;
+            }
+            (len..width).for_each(|_| cells.push(f(' ')));
+        }
+
+        Grid { width, height: lines.len(), cells }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, Point2(i, j): Point2) -> bool {
+        i >= 0 && j >= 0 && (i as usize) < self.height && (j as usize) < self.width
+    }
+
+    fn offset(&self, pos: Point2) -> Option<usize> {
+        self.in_bounds(pos).then(|| pos.0 as usize * self.width + pos.1 as usize)
+    }
+
+    pub fn get(&self, pos: Point2) -> Option<&T> {
+        self.offset(pos).map(|k| &self.cells[k])
+    }
+
+    pub fn get_mut(&mut self, pos: Point2) -> Option<&mut T> {
+        self.offset(pos).map(|k| &mut self.cells[k])
+    }
+
+    /// The value at `pos`, or `default` if `pos` lies outside the grid.
+    pub fn get_or(&self, pos: Point2, default: T) -> T
+    where T: Copy
+    {
+        self.get(pos).copied().unwrap_or(default)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Point2> {
+        let width = self.width;
+        (0..self.width * self.height).map(move |k| Point2((k / width) as isize, (k % width) as isize))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point2, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// The in-bounds orthogonal neighbours of `pos`.
+    pub fn neighbors4(&self, pos: Point2) -> impl Iterator<Item = (Point2, &T)> {
+        pos.neighbors4().into_iter().filter_map(|p| self.get(p).map(|v| (p, v)))
+    }
+
+    /// The in-bounds orthogonal and diagonal neighbours of `pos`.
+    pub fn neighbors8(&self, pos: Point2) -> impl Iterator<Item = (Point2, &T)> {
+        pos.neighbors8().into_iter().filter_map(|p| self.get(p).map(|v| (p, v)))
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        assert!(i < self.height, "row {i} is outside a grid {} high", self.height);
+        &self.cells[i * self.width..(i + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|i| self.row(i))
+    }
+
+    pub fn column(&self, j: usize) -> impl Iterator<Item = &T> {
+        assert!(j < self.width, "column {j} is outside a grid {} wide", self.width);
+        self.cells[j..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|j| self.column(j))
+    }
+
+    pub fn find(&self, mut pred: impl FnMut(&T) -> bool) -> Option<Point2> {
+        self.iter().find(|(_, v)| pred(v)).map(|(p, _)| p)
+    }
+
+    pub fn count(&self, mut pred: impl FnMut(&T) -> bool) -> usize {
+        self.cells.iter().filter(|v| pred(v)).count()
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /// Copies out the `height` by `width` block whose top-left corner is `(top, left)`.
+    pub fn subgrid(&self, top: usize, left: usize, height: usize, width: usize) -> Grid<T>
+    where T: Clone
+    {
+        assert!(left + width <= self.width && top + height <= self.height,
+                "a {height}x{width} block at ({top}, {left}) overhangs a {}x{} grid", self.height, self.width);
+
+        let cells = (top..top + height)
+            .flat_map(|i| self.row(i)[left..left + width].iter().cloned())
+            .collect();
+
+        Grid { width, height, cells }
+    }
+
+    /// Overwrites the block starting at `(top, left)` with the contents of `other`.
+    pub fn blit(&mut self, other: &Grid<T>, top: usize, left: usize)
+    where T: Clone
+    {
+        assert!(left + other.width <= self.width && top + other.height <= self.height,
+                "a {}x{} block at ({top}, {left}) overhangs a {}x{} grid",
+                other.height, other.width, self.height, self.width);
+
+        for (i, row) in other.rows().enumerate() {
+            let start = (top + i) * self.width + left;
+            self.cells[start..start + other.width].clone_from_slice(row);
+        }
+    }
+
+    /// Mirrors the grid left-to-right.
+    pub fn flip_horizontal(&self) -> Grid<T>
+    where T: Clone
+    {
+        let cells = self.rows().flat_map(|row| row.iter().rev().cloned()).collect();
+        Grid { width: self.width, height: self.height, cells }
+    }
+
+    /// Rotates the grid a quarter turn clockwise.
+    pub fn rotate_right(&self) -> Grid<T>
+    where T: Clone
+    {
+        let cells = (0..self.width)
+            .flat_map(|j| (0..self.height).rev().map(move |i| self[(i, j)].clone()))
+            .collect();
+
+        Grid { width: self.height, height: self.width, cells }
+    }
+
+    /// Renders one character per cell, one line per row.
+    pub fn render(&self, f: impl Fn(&T) -> char) -> String {
+        self.rows()
+            .map(|row| row.iter().map(&f).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        assert!(i < self.height && j < self.width, "({i}, {j}) is outside the grid");
+        &self.cells[i * self.width + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        assert!(i < self.height && j < self.width, "({i}, {j}) is outside the grid");
+        &mut self.cells[i * self.width + j]
+    }
+}
+
+impl<T> Index<Point2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Point2) -> &Self::Output {
+        self.get(pos).unwrap_or_else(|| panic!("{pos:?} is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<Point2> for Grid<T> {
+    fn index_mut(&mut self, pos: Point2) -> &mut Self::Output {
+        self.get_mut(pos).unwrap_or_else(|| panic!("{pos:?} is outside the grid"))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An unbounded grid backed by a dense buffer. The buffer's top-left corner can sit anywhere,
+/// and whenever a write escapes it the buffer doubles towards that side. Reads outside the
+/// buffer see the default value.
+#[derive(Clone, Debug)]
+pub struct InfiniteGrid<T> {
+    default: T,
+    origin: Point2,
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    bounds: Option<(Point2, Point2)>
+}
+
+impl<T: Clone> InfiniteGrid<T> {
+    pub fn new(default: T) -> InfiniteGrid<T> {
+        InfiniteGrid { default, origin: Point2(0, 0), width: 0, height: 0, cells: Vec::new(), bounds: None }
+    }
+
+    /// Copies a finite grid in, placing its top-left cell at `origin`.
+    pub fn from_grid(grid: &Grid<T>, origin: Point2, default: T) -> InfiniteGrid<T> {
+        let mut result = InfiniteGrid::new(default);
+        for (pos, value) in grid.iter() {
+            result.set(origin + pos, value.clone());
+        }
+
+        result
+    }
+
+    fn offset(&self, pos: Point2) -> Option<usize> {
+        let Point2(i, j) = pos - self.origin;
+        let in_buffer = i >= 0 && j >= 0 && (i as usize) < self.height && (j as usize) < self.width;
+        in_buffer.then(|| i as usize * self.width + j as usize)
+    }
+
+    fn grow_to_include(&mut self, pos: Point2) {
+        let Point2(mut top, mut left) = self.origin;
+        let (mut height, mut width) = (self.height as isize, self.width as isize);
+
+        if self.cells.is_empty() {
+            (top, left, height, width) = (pos.0 - 8, pos.1 - 8, 16, 16);
+        }
+        else {
+            while pos.0 < top { top -= height; height *= 2; }
+            while pos.0 >= top + height { height *= 2; }
+            while pos.1 < left { left -= width; width *= 2; }
+            while pos.1 >= left + width { width *= 2; }
+        }
+
+        let (height, width) = (height as usize, width as usize);
+        let mut cells = vec![self.default.clone(); width * height];
+        let Point2(di, dj) = self.origin - Point2(top, left);
+
+        for (i, row) in self.cells.chunks(self.width.max(1)).enumerate() {
+            let start = (di as usize + i) * width + dj as usize;
+            cells[start..start + self.width].clone_from_slice(row);
+        }
+
+        self.origin = Point2(top, left);
+        (self.width, self.height, self.cells) = (width, height, cells);
+    }
+
+    pub fn get(&self, pos: Point2) -> &T {
+        self.offset(pos).map_or(&self.default, |k| &self.cells[k])
+    }
+
+    /// Stores `value` at `pos`, growing the buffer if need be. Only writes count towards
+    /// `bounds`, so there is no `get_mut` or `IndexMut` through which a read could.
+    pub fn set(&mut self, pos: Point2, value: T) {
+        let k = match self.offset(pos) {
+            Some(k) => k,
+            None => {
+                self.grow_to_include(pos);
+                self.offset(pos).unwrap()
+            }
+        };
+
+        self.cells[k] = value;
+        self.bounds = Some(match self.bounds {
+            None => (pos, pos),
+            Some((lo, hi)) => (
+                Point2(lo.0.min(pos.0), lo.1.min(pos.1)),
+                Point2(hi.0.max(pos.0), hi.1.max(pos.1))
+            )
+        });
+    }
+
+    /// The smallest box (top-left and bottom-right corners, inclusive) containing every
+    /// position passed to `set`, even where the default was written.
+    pub fn bounds(&self) -> Option<(Point2, Point2)> {
+        self.bounds
+    }
+
+    /// Every position inside `bounds` whose value differs from the default.
+    pub fn populated(&self) -> impl Iterator<Item = (Point2, &T)>
+    where T: PartialEq
+    {
+        let (lo, hi) = self.bounds.unwrap_or((Point2(0, 0), Point2(-1, -1)));
+
+        (lo.0..=hi.0)
+            .flat_map(move |i| (lo.1..=hi.1).map(move |j| Point2(i, j)))
+            .map(|pos| (pos, self.get(pos)))
+            .filter(|(_, v)| **v != self.default)
+    }
+
+    /// Copies the written area out as a finite grid, together with the position of its
+    /// top-left cell.
+    pub fn to_grid(&self) -> (Point2, Grid<T>) {
+        match self.bounds {
+            None => (Point2(0, 0), Grid::from_vec(0, 0, Vec::new())),
+            Some((lo, hi)) => {
+                let Point2(rows, cols) = hi - lo + Point2(1, 1);
+                let cells = (lo.0..=hi.0)
+                    .flat_map(|i| (lo.1..=hi.1).map(move |j| Point2(i, j)))
+                    .map(|pos| self.get(pos).clone())
+                    .collect();
+
+                (lo, Grid::from_vec(cols as usize, rows as usize, cells))
+            }
+        }
+    }
+}
+
+impl<T: Clone> Index<Point2> for InfiniteGrid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Point2) -> &Self::Output {
+        self.get(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let grid = Grid::from_str("ab\ncd\nef", |c| c);
+
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!(grid[Point2(2, 1)], 'f');
+        assert_eq!(grid.get_or(Point2(-1, 0), ' '), ' ');
+        assert_eq!(grid.column(1).collect::<String>(), "bdf");
+        assert_eq!(grid.neighbors4(Point2(0, 0)).count(), 2);
+        assert_eq!(grid.to_string(), "ab\ncd\nef");
+        assert_eq!(grid.rotate_right().to_string(), "eca\nfdb");
+        assert_eq!(grid.flip_horizontal().to_string(), "ba\ndc\nfe");
+
+        let mut big = Grid::new(4, 4, '.');
+        big.blit(&grid.subgrid(1, 0, 2, 2), 1, 1);
+        assert_eq!(big.to_string(), "....\n.cd.\n.ef.\n....");
+
+        let ragged = Grid::from_str("a\nbcd", |c| c);
+        assert_eq!(ragged.row(0), &['a', ' ', ' ']);
+    }
+
+    #[test]
+    fn test_infinite_grid() {
+        let mut grid = InfiniteGrid::new(0);
+        assert_eq!(grid.bounds(), None);
+
+        grid.set(Point2(0, 0), 1);
+        grid.set(Point2(-100, 3), 2);
+        grid.set(Point2(50, -70), 3);
+        grid.set(Point2(1, 1), grid[Point2(1, 1)] + 4);
+
+        assert_eq!(grid[Point2(0, 0)], 1);
+        assert_eq!(grid[Point2(-100, 3)], 2);
+        assert_eq!(grid[Point2(50, -70)], 3);
+        assert_eq!(grid[Point2(1000, 1000)], 0);
+        assert_eq!(grid.bounds(), Some((Point2(-100, -70), Point2(50, 3))));
+
+        let _ = grid.get(Point2(-500, 0));
+        assert_eq!(grid.bounds(), Some((Point2(-100, -70), Point2(50, 3))));
+        assert_eq!(grid.populated().map(|(_, &v)| v).sum::<i32>(), 10);
+
+        let (origin, finite) = grid.to_grid();
+        assert_eq!(origin, Point2(-100, -70));
+        assert_eq!((finite.height(), finite.width()), (151, 74));
+        assert_eq!(finite[Point2(100, 70)], 1);
+    }
+}
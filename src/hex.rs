@@ -0,0 +1,264 @@
+use std::ops::{Add, AddAssign, Mul, Sub};
+use std::str::FromStr;
+use crate::geom::Point2;
+
+/// A hex in axial coordinates `Point2(q, r)` on a flat-topped grid; the implied third cube
+/// coordinate is `s = -q - r`. North is `(0, -1)`. Only the `Point2` arithmetic that means the
+/// same on hexes is exposed; distances and turns have their own hex versions.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Hex(Point2);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum HexDirection { N, NE, SE, S, SW, NW }
+
+/// The four ways of laying a hex grid out in rows and columns: which axis is staggered, and
+/// whether the odd or even rows (columns) are pushed out.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Offset { OddR, EvenR, OddQ, EvenQ }
+
+impl HexDirection {
+    /// Clockwise from north.
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::N, HexDirection::NE, HexDirection::SE,
+        HexDirection::S, HexDirection::SW, HexDirection::NW
+    ];
+
+    pub fn to_hex(self) -> Hex {
+        match self {
+            HexDirection::N => Hex::new(0, -1),
+            HexDirection::NE => Hex::new(1, -1),
+            HexDirection::SE => Hex::new(1, 0),
+            HexDirection::S => Hex::new(0, 1),
+            HexDirection::SW => Hex::new(-1, 1),
+            HexDirection::NW => Hex::new(-1, 0)
+        }
+    }
+
+    pub fn turn_right(self) -> HexDirection {
+        HexDirection::ALL[(self as usize + 1) % 6]
+    }
+
+    pub fn turn_left(self) -> HexDirection {
+        HexDirection::ALL[(self as usize + 5) % 6]
+    }
+
+    pub fn reverse(self) -> HexDirection {
+        HexDirection::ALL[(self as usize + 3) % 6]
+    }
+}
+
+impl FromStr for HexDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "n" => Ok(HexDirection::N),
+            "ne" => Ok(HexDirection::NE),
+            "se" => Ok(HexDirection::SE),
+            "s" => Ok(HexDirection::S),
+            "sw" => Ok(HexDirection::SW),
+            "nw" => Ok(HexDirection::NW),
+            _ => Err(format!("invalid hex direction {s:?}"))
+        }
+    }
+}
+
+impl Hex {
+    pub const fn new(q: isize, r: isize) -> Hex {
+        Hex(Point2(q, r))
+    }
+
+    pub fn q(self) -> isize {
+        self.0.0
+    }
+
+    pub fn r(self) -> isize {
+        self.0.1
+    }
+
+    pub fn origin() -> Hex {
+        Hex::new(0, 0)
+    }
+
+    pub fn from_cube(q: isize, r: isize, s: isize) -> Hex {
+        assert_eq!(q + r + s, 0, "cube coordinates must sum to zero");
+        Hex::new(q, r)
+    }
+
+    pub fn to_cube(self) -> (isize, isize, isize) {
+        let Point2(q, r) = self.0;
+        (q, r, -q - r)
+    }
+
+    /// Converts from `Point2(col, row)` in the given offset layout.
+    pub fn from_offset(Point2(col, row): Point2, layout: Offset) -> Hex {
+        match layout {
+            Offset::OddR => Hex::new(col - (row - (row & 1)) / 2, row),
+            Offset::EvenR => Hex::new(col - (row + (row & 1)) / 2, row),
+            Offset::OddQ => Hex::new(col, row - (col - (col & 1)) / 2),
+            Offset::EvenQ => Hex::new(col, row - (col + (col & 1)) / 2)
+        }
+    }
+
+    /// Converts to `Point2(col, row)` in the given offset layout.
+    pub fn to_offset(self, layout: Offset) -> Point2 {
+        let Point2(q, r) = self.0;
+        match layout {
+            Offset::OddR => Point2(q + (r - (r & 1)) / 2, r),
+            Offset::EvenR => Point2(q + (r + (r & 1)) / 2, r),
+            Offset::OddQ => Point2(q, r + (q - (q & 1)) / 2),
+            Offset::EvenQ => Point2(q, r + (q + (q & 1)) / 2)
+        }
+    }
+
+    /// The number of steps from the origin.
+    pub fn num_steps(self) -> isize {
+        let (q, r, s) = self.to_cube();
+        (q.abs() + r.abs() + s.abs()) / 2
+    }
+
+    pub fn distance(self, other: Hex) -> isize {
+        (self - other).num_steps()
+    }
+
+    pub fn neighbor(self, dir: HexDirection) -> Hex {
+        self + dir.to_hex()
+    }
+
+    pub fn neighbors(self) -> [Hex; 6] {
+        HexDirection::ALL.map(|dir| self.neighbor(dir))
+    }
+
+    /// The hexes exactly `radius` steps away, clockwise starting from the one due south-west.
+    pub fn ring(self, radius: usize) -> Vec<Hex> {
+        if radius == 0 {
+            return vec![self];
+        }
+
+        let mut cur = self + HexDirection::SW.to_hex() * radius as isize;
+        let mut result = Vec::with_capacity(6 * radius);
+
+        for dir in HexDirection::ALL {
+            for _ in 0..radius {
+                result.push(cur);
+                cur += dir.to_hex();
+            }
+        }
+
+        result
+    }
+
+    /// Every hex within `radius` steps, ring by ring outwards.
+    pub fn spiral(self, radius: usize) -> Vec<Hex> {
+        (0..=radius).flat_map(|k| self.ring(k)).collect()
+    }
+
+    /// The hexes on the straight line from `self` to `other`, both ends included.
+    pub fn line_to(self, other: Hex) -> Vec<Hex> {
+        let n = self.distance(other);
+        if n == 0 {
+            return vec![self];
+        }
+
+        // Nudge off the exact midpoints so that ties round consistently.
+        let (q0, r0) = (self.q() as f64 + 1e-6, self.r() as f64 + 2e-6);
+        let (q1, r1) = (other.q() as f64 + 1e-6, other.r() as f64 + 2e-6);
+
+        (0..=n)
+            .map(|i| {
+                let t = i as f64 / n as f64;
+                round(q0 + (q1 - q0) * t, r0 + (r1 - r0) * t)
+            })
+            .collect()
+    }
+
+    /// A shortest sequence of steps leading from `self` to `other`.
+    pub fn steps_to(self, other: Hex) -> Vec<HexDirection> {
+        let mut cur = self;
+        let mut result = Vec::with_capacity(self.distance(other) as usize);
+
+        while cur != other {
+            let dir = HexDirection::ALL.into_iter()
+                .min_by_key(|&dir| cur.neighbor(dir).distance(other))
+                .unwrap();
+            result.push(dir);
+            cur = cur.neighbor(dir);
+        }
+
+        result
+    }
+}
+
+fn round(q: f64, r: f64) -> Hex {
+    let s = -q - r;
+    let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+    let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+
+    if dq > dr && dq > ds {
+        rq = -rr - rs;
+    }
+    else if dr > ds {
+        rr = -rq - rs;
+    }
+
+    Hex::new(rq as isize, rr as isize)
+}
+
+impl Add for Hex {
+    type Output = Hex;
+    fn add(self, rhs: Self) -> Self::Output {
+        Hex(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Hex {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Hex(self.0 - rhs.0)
+    }
+}
+
+impl Mul<isize> for Hex {
+    type Output = Hex;
+    fn mul(self, rhs: isize) -> Self::Output {
+        Hex(self.0 * rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex() {
+        let h = Hex::new(2, -3);
+        assert_eq!((h.q(), h.r()), (2, -3));
+
+        assert_eq!(h.num_steps(), 3);
+        assert!(h.neighbors().iter().all(|&n| n.distance(h) == 1));
+        assert_eq!(Hex::origin().ring(2).len(), 12);
+        assert!(Hex::origin().ring(2).iter().all(|n| n.num_steps() == 2));
+        assert_eq!(h.spiral(2).len(), 19);
+
+        for layout in [Offset::OddR, Offset::EvenR, Offset::OddQ, Offset::EvenQ] {
+            for hex in Hex::origin().spiral(3) {
+                assert_eq!(Hex::from_offset(hex.to_offset(layout), layout), hex);
+            }
+        }
+
+        let line = Hex::new(-2, 0).line_to(Hex::new(3, -4));
+        assert_eq!(line.len(), 6);
+        assert!(line.windows(2).all(|w| w[0].distance(w[1]) == 1));
+
+        let steps = Hex::new(-2, 0).steps_to(Hex::new(3, -4));
+        assert_eq!(steps.len(), 5);
+        let end = steps.iter().fold(Hex::new(-2, 0), |cur, &dir| cur.neighbor(dir));
+        assert_eq!(end, Hex::new(3, -4));
+    }
+}
@@ -1,33 +1,139 @@
+pub mod animation;
+pub mod bitset;
+pub mod checkpoint;
+pub mod cycle;
+pub mod disjoint_set;
+pub mod geom;
+pub mod graph;
+pub mod grid;
+pub mod hex;
 pub mod knothash;
+pub mod math;
+pub mod parse;
+pub mod permutation;
+pub mod render;
+pub mod runner;
+pub mod simulation;
+pub mod spiral;
 
+/// Builds a `main` that registers each part's solver and hands off to `Registry::main`.
+///
+/// A part is either a single solver or a braced list of named strategies, the first of which
+/// is the default:
+///
+/// ```ignore
+/// build_main!("day15.txt", "Part 1" => { "u128" => part1, "mersenne" => part1_fast });
+/// ```
+///
+/// A part of variants, strategies that each compute something different and so are not
+/// expected to agree under `compare`, may follow after a semicolon, given as named
+/// `VariantFn`s; `run` only runs one when it is named:
+///
+/// ```ignore
+/// build_main_res!("day02.txt", "Part 1" => part1; variants "Checksum" => checksum_variants());
+/// ```
+///
+/// Renderers for the `render` command follow the parts after a semicolon:
+///
+/// ```ignore
+/// build_main!("day14.txt", "Part 1" => part1; render "disk" => render_disk);
+/// ```
+///
+/// Then a handler for the `checkpoint` command, which saves progress into the directory named
+/// on the command line and resumes from it:
+///
+/// ```ignore
+/// build_main!("day25.txt", "Part 1" => part1; checkpoint checkpoint_part1);
+/// ```
+///
+/// Finally, a handler for the `stream` command, which reads the input from stdin instead:
+///
+/// ```ignore
+/// build_main_res!("day01.txt", "Part 1" => part1; stream stream_captchas);
+/// ```
 #[macro_export]
 macro_rules! build_main {
-    ($input:literal, $( $part:literal => $solver:expr),+) => {
-        use std::time::Instant;
+    ($input:literal, $( $part:literal => { $( $name:literal => $solver:expr ),+ } ),+
+     $(; variants $vpart:literal => $variants:expr )?
+     $(; render $( $renderer:literal => $render:expr ),+ )? $(; checkpoint $checkpoint:expr )?
+     $(; stream $stream:expr )?) => {
         fn main() {
             let input: &str = include_str!(concat!("../input/", $input));
+            let mut registry = $crate::runner::Registry::new();
+            $($(
+            registry.register($part, $name, |input: &str| $solver(input).to_string());
+            )+)+
             $(
-            let start = Instant::now();
-            let result = $solver(input);
-            let duration = start.elapsed().as_micros();
-            println!("{}: {} (Time: {}μs)", $part, result, duration);
-            )+
+            for (name, solver) in $variants {
+                registry.register_variant($vpart, name, solver);
+            }
+            )?
+            $($(
+            registry.register_renderer($renderer, $render);
+            )+)?
+            $(
+            registry.register_checkpoint($checkpoint);
+            )?
+            $(
+            registry.register_stream($stream);
+            )?
+            registry.main(input);
         }
     };
+    ($input:literal, $( $part:literal => $solver:expr ),+
+     $(; variants $vpart:literal => $variants:expr )?
+     $(; render $( $renderer:literal => $render:expr ),+ )? $(; checkpoint $checkpoint:expr )?
+     $(; stream $stream:expr )?) => {
+        $crate::build_main!($input, $( $part => { "default" => $solver } ),+
+            $(; variants $vpart => $variants )?
+            $(; render $( $renderer => $render ),+ )? $(; checkpoint $checkpoint )? $(; stream $stream )?);
+    };
 }
 
+/// Like `build_main!`, for solvers returning a `Result`: an `Err` is reported on stderr and
+/// the process exits with status 1.
 #[macro_export]
 macro_rules! build_main_res {
-    ($input:literal, $( $part:literal => $solver:expr),+) => {
-        use std::time::Instant;
+    ($input:literal, $( $part:literal => { $( $name:literal => $solver:expr ),+ } ),+
+     $(; variants $vpart:literal => $variants:expr )?
+     $(; render $( $renderer:literal => $render:expr ),+ )? $(; checkpoint $checkpoint:expr )?
+     $(; stream $stream:expr )?) => {
         fn main() {
             let input: &str = include_str!(concat!("../input/", $input));
+            let mut registry = $crate::runner::Registry::new();
+            $($(
+            registry.register($part, $name, |input: &str| {
+                $solver(input)
+                    .unwrap_or_else(|e| {
+                        eprintln!("{}: {}", $part, e);
+                        std::process::exit(1)
+                    })
+                    .to_string()
+            });
+            )+)+
             $(
-            let start = Instant::now();
-            let result = $solver(input).unwrap();
-            let duration = start.elapsed().as_micros();
-            println!("{}: {} (Time: {}μs)", $part, result, duration);
-            )+
+            for (name, solver) in $variants {
+                registry.register_variant($vpart, name, solver);
+            }
+            )?
+            $($(
+            registry.register_renderer($renderer, $render);
+            )+)?
+            $(
+            registry.register_checkpoint($checkpoint);
+            )?
+            $(
+            registry.register_stream($stream);
+            )?
+            registry.main(input);
         }
     };
+    ($input:literal, $( $part:literal => $solver:expr ),+
+     $(; variants $vpart:literal => $variants:expr )?
+     $(; render $( $renderer:literal => $render:expr ),+ )? $(; checkpoint $checkpoint:expr )?
+     $(; stream $stream:expr )?) => {
+        $crate::build_main_res!($input, $( $part => { "default" => $solver } ),+
+            $(; variants $vpart => $variants )?
+            $(; render $( $renderer => $render ),+ )? $(; checkpoint $checkpoint )? $(; stream $stream )?);
+    };
 }
@@ -0,0 +1,342 @@
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+/// The primitive integer types, for the generic helpers below.
+pub trait Integer: Copy + Ord + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+    + Div<Output = Self> + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+}
+
+macro_rules! impl_integer {
+    (unsigned: $($u:ty),+; signed: $($s:ty),+) => {
+        $(
+        impl Integer for $u {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn abs(self) -> Self {
+                self
+            }
+        }
+        )+
+        $(
+        impl Integer for $s {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn abs(self) -> Self {
+                <$s>::abs(self)
+            }
+        }
+        )+
+    };
+}
+
+impl_integer!(unsigned: u8, u16, u32, u64, u128, usize; signed: i8, i16, i32, i64, i128, isize);
+
+/// The non-negative greatest common divisor; `gcd(0, 0) == 0`.
+pub fn gcd<T: Integer>(mut a: T, mut b: T) -> T {
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+
+    a.abs()
+}
+
+/// The non-negative least common multiple; zero if either argument is.
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    if a == T::ZERO || b == T::ZERO {
+        return T::ZERO;
+    }
+
+    (a / gcd(a, b) * b).abs()
+}
+
+/// Returns `(g, x, y)` with `a*x + b*y == g == gcd(a, b)`.
+pub fn extended_gcd<T: Integer + Neg<Output = T>>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+
+    while r != T::ZERO {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < T::ZERO {
+        (-old_r, -old_x, -old_y)
+    }
+    else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The inverse of `a` modulo `m`, in `0..m`, if `a` and `m` are coprime.
+pub fn mod_inverse<T: Integer + Neg<Output = T>>(a: T, m: T) -> Option<T> {
+    let (g, x, _) = extended_gcd(a % m, m);
+    (g == T::ONE).then(|| ((x % m) + m) % m)
+}
+
+/// `a * b mod m` without overflowing, for any 64-bit operands.
+pub fn mulmod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+/// `base^exp mod m` by repeated squaring.
+pub fn modpow(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1 % m;
+    base %= m;
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mulmod(result, base, m);
+        }
+        base = mulmod(base, base, m);
+        exp >>= 1;
+    }
+
+    result
+}
+
+/// Solves the system `x = r (mod m)` over the given `(r, m)` pairs, where the moduli need not
+/// be coprime. Returns the smallest non-negative solution and the combined modulus, or `None`
+/// if the congruences are inconsistent or the combined modulus does not fit in an `i64`.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut x: i128 = 0;
+    let mut m: i128 = 1;
+
+    for &(r, n) in congruences {
+        let (r, n) = (r as i128, n as i128);
+        let (g, p, _) = extended_gcd(m, n);
+
+        if (r - x) % g != 0 {
+            return None;
+        }
+
+        let step = n / g;
+        let k = ((r - x) / g % step * p % step + step) % step;
+        x += m * k;
+        m = i64::try_from(m * step).ok()? as i128;
+        x = x.rem_euclid(m);
+    }
+
+    Some((i64::try_from(x).ok()?, i64::try_from(m).ok()?))
+}
+
+/// The floor of the square root of `n`, computed exactly.
+pub fn isqrt(n: u64) -> u64 {
+    let mut x = (n as f64).sqrt() as u64;
+
+    while x.checked_mul(x).is_none_or(|sq| sq > n) {
+        x -= 1;
+    }
+    while (x + 1).checked_mul(x + 1).is_some_and(|sq| sq <= n) {
+        x += 1;
+    }
+
+    x
+}
+
+/// The square root of `n` if `n` is a perfect square.
+pub fn perfect_sqrt(n: u64) -> Option<u64> {
+    let x = isqrt(n);
+    (x * x == n).then_some(x)
+}
+
+/// Deterministic Miller-Rabin: these witnesses are enough for every 64-bit `n`.
+pub fn is_prime(n: u64) -> bool {
+    const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+
+    for p in WITNESSES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    WITNESSES.iter().all(|&a| {
+        let mut x = modpow(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+
+        (1..s).any(|_| {
+            x = mulmod(x, x, n);
+            x == n - 1
+        })
+    })
+}
+
+/// Sieve of Eratosthenes: `result[k]` says whether `k` is prime, for `k <= limit`.
+pub fn sieve(limit: usize) -> Vec<bool> {
+    let mut result = vec![true; limit + 1];
+    result[0] = false;
+    if limit >= 1 {
+        result[1] = false;
+    }
+
+    let mut p = 2;
+    while p * p <= limit {
+        if result[p] {
+            (p * p..=limit).step_by(p).for_each(|k| result[k] = false);
+        }
+        p += 1;
+    }
+
+    result
+}
+
+pub fn primes_up_to(limit: usize) -> Vec<usize> {
+    sieve(limit).into_iter()
+        .enumerate()
+        .filter(|&(_, is_prime)| is_prime)
+        .map(|(p, _)| p)
+        .collect()
+}
+
+/// An in-place radix-2 fast Fourier transform of the complex sequence `re + i im`, whose
+/// length must be a power of two. With `inverse` set, the inverse transform, scaled by `1 / n`.
+pub fn fft(re: &mut [f64], im: &mut [f64], inverse: bool) {
+    let n = re.len();
+    assert!(n.is_power_of_two() && im.len() == n, "an FFT needs equal lengths that are a power of two");
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let angle = sign * 2.0 * std::f64::consts::PI * k as f64 / len as f64;
+                let (wr, wi) = (angle.cos(), angle.sin());
+                let (a, b) = (start + k, start + k + len / 2);
+                let (tr, ti) = (re[b] * wr - im[b] * wi, re[b] * wi + im[b] * wr);
+
+                (re[b], im[b]) = (re[a] - tr, im[a] - ti);
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+
+    if inverse {
+        re.iter_mut().chain(im.iter_mut()).for_each(|x| *x /= n as f64);
+    }
+}
+
+/// `result[k]` is the sum of `signal[i] * signal[(i + k) % n]` over every `i`, computed in
+/// O(n log n) with an FFT padded far enough that the wrap-around can be folded back in.
+pub fn circular_autocorrelation(signal: &[f64]) -> Vec<f64> {
+    let n = signal.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let m = (2 * n).next_power_of_two();
+    let mut re = signal.to_vec();
+    re.resize(m, 0.0);
+    let mut im = vec![0.0; m];
+
+    fft(&mut re, &mut im, false);
+    for k in 0..m {
+        (re[k], im[k]) = (re[k] * re[k] + im[k] * im[k], 0.0);
+    }
+    fft(&mut re, &mut im, true);
+
+    // Padded, index `j` holds the lag `j` and index `m - j` the lag `-j`; a circular lag of
+    // `k` is the linear lags `k` and `k - n` together.
+    (0..n).map(|k| re[k] + re[m - n + k]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12u32, 18), 6);
+        assert_eq!(gcd(-12i64, 18), 6);
+        assert_eq!(gcd(0usize, 0), 0);
+        assert_eq!(lcm(4usize, 6), 12);
+        assert_eq!(lcm(-4i32, 6), 12);
+
+        let (g, x, y) = extended_gcd(240i64, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+
+        assert_eq!(mod_inverse(3i64, 11), Some(4));
+        assert_eq!(mod_inverse(4i64, 8), None);
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mulmod(u64::MAX, u64::MAX, 1_000_000_007), ((u64::MAX as u128).pow(2) % 1_000_000_007) as u64);
+        assert_eq!(modpow(2, 10, 1000), 24);
+        assert_eq!(modpow(16807, 2147483646, 2147483647), 1);
+
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(0, 1 << 40), (1, (1 << 40) + 1)]), None);
+    }
+
+    #[test]
+    fn test_roots_and_primes() {
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+        assert_eq!(perfect_sqrt(1 << 62), Some(1 << 31));
+        assert_eq!(perfect_sqrt((1 << 62) + 1), None);
+
+        let primes = primes_up_to(1000);
+        assert_eq!(primes.len(), 168);
+        assert!((0..=1000).all(|n| is_prime(n) == primes.contains(&(n as usize))));
+        assert!(is_prime(2147483647));
+        assert!(is_prime(18446744073709551557));
+        assert!(!is_prime(3215031751));
+    }
+
+    #[test]
+    fn test_fft() {
+        let signal = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0];
+        let n = signal.len();
+        let naive = (0..n)
+            .map(|k| (0..n).map(|i| signal[i] * signal[(i + k) % n]).sum::<f64>())
+            .collect::<Vec<_>>();
+
+        let fast = circular_autocorrelation(&signal);
+        assert!(fast.iter().zip(naive.iter()).all(|(a, b)| (a - b).abs() < 1e-9));
+
+        let (mut re, mut im) = (vec![1.0, 2.0, 3.0, 4.0], vec![0.0; 4]);
+        fft(&mut re, &mut im, false);
+        assert!((re[0] - 10.0).abs() < 1e-9 && (im[1] - 2.0).abs() < 1e-9);
+        fft(&mut re, &mut im, true);
+        assert!(re.iter().zip([1.0, 2.0, 3.0, 4.0]).all(|(a, b)| (a - b).abs() < 1e-9));
+    }
+}
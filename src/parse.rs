@@ -0,0 +1,111 @@
+use std::str::FromStr;
+use nom::branch::alt;
+use nom::character::complete::{char, digit1, line_ending, none_of, space0, space1};
+use nom::combinator::{map, map_opt, map_res, opt, recognize, verify};
+use nom::error::Error;
+use nom::multi::{many1, separated_list1};
+use nom::sequence::{pair, terminated, tuple};
+use nom::{IResult, Parser};
+use crate::grid::Grid;
+
+/// An unsigned decimal integer of any width.
+pub fn unsigned<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(digit1, |s: &str| s.parse::<T>())(input)
+}
+
+/// A decimal integer of any width with an optional leading `-` or `+`.
+pub fn signed<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(
+        recognize(pair(opt(alt((char('-'), char('+')))), digit1)),
+        |s: &str| s.parse::<T>()
+    )(input)
+}
+
+/// One or more `item`s separated by commas, each optionally followed by spaces.
+pub fn comma_separated<'a, O, F>(item: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where F: Parser<&'a str, O, Error<&'a str>>
+{
+    separated_list1(tuple((char(','), space0)), item)
+}
+
+/// One or more `item`s separated by runs of spaces and/or tabs.
+pub fn space_separated<'a, O, F>(item: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where F: Parser<&'a str, O, Error<&'a str>>
+{
+    separated_list1(space1, item)
+}
+
+/// One or more `item`s separated either by commas, with optional spaces or tabs either side,
+/// or by runs of spaces and/or tabs, so that whitespace-aligned and CSV rows both parse.
+pub fn fields<'a, O, F>(item: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where F: Parser<&'a str, O, Error<&'a str>>
+{
+    separated_list1(alt((recognize(tuple((space0, char(','), space0))), space1)), item)
+}
+
+/// One or more `item`s separated by single tabs.
+pub fn tab_separated<'a, O, F>(item: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where F: Parser<&'a str, O, Error<&'a str>>
+{
+    separated_list1(char('\t'), item)
+}
+
+/// One `line` per line of input, allowing (but not requiring) a final newline.
+pub fn lines<'a, O, F>(line: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where F: Parser<&'a str, O, Error<&'a str>>
+{
+    terminated(separated_list1(line_ending, line), opt(line_ending))
+}
+
+/// A rectangular block of characters, one row per line, with each character mapped by `cell`;
+/// fails on characters `cell` rejects and on rows of differing lengths.
+pub fn grid<'a, T, F>(cell: F) -> impl FnMut(&'a str) -> IResult<&'a str, Grid<T>>
+where F: Fn(char) -> Option<T> + Copy
+{
+    map(
+        verify(
+            lines(many1(map_opt(none_of("\r\n"), cell))),
+            |rows: &Vec<Vec<T>>| rows.iter().all(|row| row.len() == rows[0].len())
+        ),
+        Grid::from_rows
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::combinator::all_consuming;
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(unsigned::<u8>("255 rest"), Ok((" rest", 255)));
+        assert!(unsigned::<u8>("256").is_err());
+        assert!(unsigned::<u32>("-1").is_err());
+        assert_eq!(signed::<i64>("-42,"), Ok((",", -42)));
+        assert_eq!(signed::<i8>("+7"), Ok(("", 7)));
+    }
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(comma_separated(signed::<i32>)("1, -2,3"), Ok(("", vec![1, -2, 3])));
+        assert_eq!(space_separated(unsigned::<u32>)("5 \t1  9"), Ok(("", vec![5, 1, 9])));
+        assert_eq!(tab_separated(unsigned::<u32>)("5\t1 9"), Ok((" 9", vec![5, 1])));
+        assert_eq!(fields(unsigned::<u32>)("5\t1  9"), Ok(("", vec![5, 1, 9])));
+        assert_eq!(fields(unsigned::<u32>)("5,1 , 9,\t2"), Ok(("", vec![5, 1, 9, 2])));
+        assert_eq!(fields(unsigned::<u32>)("5,,1"), Ok((",,1", vec![5])));
+
+        let mut rows = all_consuming(lines(space_separated(unsigned::<u32>)));
+        assert_eq!(rows("1 2\n3\n").unwrap().1, vec![vec![1, 2], vec![3]]);
+        assert_eq!(rows("1 2\r\n3").unwrap().1, vec![vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn test_grid() {
+        let cell = |c| match c { '#' => Some(true), '.' => Some(false), _ => None };
+
+        let (_, g) = grid(cell)("#.\n.#\n").unwrap();
+        assert_eq!(g.count(|&b| b), 2);
+        assert!(all_consuming(grid(cell))("#.\n.").is_err());
+        assert!(all_consuming(grid(cell))("#x").is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use std::ops::Index;
+use crate::math::lcm;
+
+/// A permutation of `0..len`, acting on a slice by moving the element at index `self[i]` to
+/// index `i`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Permutation(Vec<usize>);
+
+impl Permutation {
+    pub fn identity(len: usize) -> Permutation {
+        Permutation((0..len).collect())
+    }
+
+    /// Wraps `images` if it holds each of `0..images.len()` exactly once.
+    pub fn from_vec(images: Vec<usize>) -> Option<Permutation> {
+        let mut seen = vec![false; images.len()];
+        for &i in images.iter() {
+            if i >= seen.len() || seen[i] {
+                return None;
+            }
+            seen[i] = true;
+        }
+
+        Some(Permutation(images))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[usize] {
+        &self.0
+    }
+
+    /// The rearranged copy of `items`, whose `i`th element is `items[self[i]]`.
+    pub fn apply<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        assert_eq!(items.len(), self.len(), "permutation applied to a slice of the wrong length");
+        self.0.iter().map(|&i| items[i].clone()).collect()
+    }
+
+    /// The permutation whose `apply` is `self.apply` followed by `other.apply`.
+    pub fn compose(&self, other: &Permutation) -> Permutation {
+        assert_eq!(self.len(), other.len(), "composed permutations of different lengths");
+        Permutation(other.0.iter().map(|&i| self.0[i]).collect())
+    }
+
+    pub fn inverse(&self) -> Permutation {
+        let mut result = vec![0; self.len()];
+        self.0.iter().enumerate().for_each(|(i, &j)| result[j] = i);
+        Permutation(result)
+    }
+
+    /// `self` composed with itself `exp` times, by repeated squaring.
+    pub fn pow(&self, mut exp: u64) -> Permutation {
+        let mut result = Permutation::identity(self.len());
+        let mut base = self.clone();
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.compose(&base);
+            }
+            base = base.compose(&base);
+            exp >>= 1;
+        }
+
+        result
+    }
+
+    /// The disjoint cycles, fixed points included, each starting from its smallest member.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.len()];
+        let mut result = Vec::new();
+
+        for i in 0..self.len() {
+            if seen[i] {
+                continue;
+            }
+
+            let mut cycle = Vec::new();
+            let mut j = i;
+            while !seen[j] {
+                seen[j] = true;
+                cycle.push(j);
+                j = self.0[j];
+            }
+            result.push(cycle);
+        }
+
+        result
+    }
+
+    /// The smallest positive power of `self` that is the identity.
+    pub fn order(&self) -> usize {
+        self.cycles().iter().map(|cycle| cycle.len()).fold(1, lcm)
+    }
+}
+
+impl Index<usize> for Permutation {
+    type Output = usize;
+
+    fn index(&self, i: usize) -> &usize {
+        &self.0[i]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permutation() {
+        let p = Permutation::from_vec(vec![1, 2, 0, 4, 3]).unwrap();
+        assert!(Permutation::from_vec(vec![0, 0]).is_none());
+        assert!(Permutation::from_vec(vec![2, 0]).is_none());
+
+        assert_eq!(p.apply(&['a', 'b', 'c', 'd', 'e']), vec!['b', 'c', 'a', 'e', 'd']);
+        assert_eq!(p.cycles(), vec![vec![0, 1, 2], vec![3, 4]]);
+        assert_eq!(p.order(), 6);
+        assert_eq!(p.compose(&p.inverse()), Permutation::identity(5));
+        assert_eq!(p.pow(6), Permutation::identity(5));
+        assert_eq!(p.pow(1_000_000_001), p.pow(5));
+
+        let q = Permutation::from_vec(vec![4, 3, 2, 1, 0]).unwrap();
+        let items = [10, 20, 30, 40, 50];
+        assert_eq!(p.compose(&q).apply(&items), q.apply(&p.apply(&items)));
+        assert_eq!(p.pow(3).apply(&items), p.apply(&p.apply(&p.apply(&items))));
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use crate::grid::Grid;
+
+/// A 24-bit colour.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const RED: Rgb = Rgb(255, 0, 0);
+    pub const GREEN: Rgb = Rgb(0, 255, 0);
+    pub const BLUE: Rgb = Rgb(0, 0, 255);
+    pub const YELLOW: Rgb = Rgb(255, 255, 0);
+
+    pub fn gray(level: u8) -> Rgb {
+        Rgb(level, level, level)
+    }
+}
+
+/// The rows of pixels making up `grid` drawn with each cell as a `scale` by `scale` block.
+fn pixel_rows<T>(grid: &Grid<T>, scale: usize) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+    assert!(scale > 0, "images must be drawn at a positive scale");
+
+    grid.rows()
+        .flat_map(move |row| (0..scale).map(move |_| row))
+        .map(move |row| row.iter().flat_map(move |cell| (0..scale).map(move |_| cell)))
+}
+
+/// One character per cell, one line per row, with a trailing newline.
+pub fn write_ascii<T>(out: &mut impl Write, grid: &Grid<T>, f: impl Fn(&T) -> char) -> io::Result<()> {
+    writeln!(out, "{}", grid.render(f))
+}
+
+/// A binary (P4) bitmap; cells mapped to `true` are drawn black.
+pub fn write_pbm<T>(out: &mut impl Write, grid: &Grid<T>, scale: usize, f: impl Fn(&T) -> bool) -> io::Result<()> {
+    write!(out, "P4\n{} {}\n", grid.width() * scale, grid.height() * scale)?;
+
+    for row in pixel_rows(grid, scale) {
+        let mut bytes = Vec::with_capacity((grid.width() * scale).div_ceil(8));
+        for (k, cell) in row.enumerate() {
+            if k % 8 == 0 {
+                bytes.push(0);
+            }
+            if f(cell) {
+                *bytes.last_mut().unwrap() |= 0x80 >> (k % 8);
+            }
+        }
+        out.write_all(&bytes)?;
+    }
+
+    Ok(())
+}
+
+/// A binary (P5) 8-bit graymap, where 0 is black.
+pub fn write_pgm<T>(out: &mut impl Write, grid: &Grid<T>, scale: usize, f: impl Fn(&T) -> u8) -> io::Result<()> {
+    write!(out, "P5\n{} {}\n255\n", grid.width() * scale, grid.height() * scale)?;
+
+    for row in pixel_rows(grid, scale) {
+        out.write_all(&row.map(&f).collect::<Vec<_>>())?;
+    }
+
+    Ok(())
+}
+
+/// A binary (P6) 24-bit pixmap.
+pub fn write_ppm<T>(out: &mut impl Write, grid: &Grid<T>, scale: usize, f: impl Fn(&T) -> Rgb) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", grid.width() * scale, grid.height() * scale)?;
+
+    for row in pixel_rows(grid, scale) {
+        let bytes = row.flat_map(|cell| {
+                let Rgb(r, g, b) = f(cell);
+                [r, g, b]
+            })
+            .collect::<Vec<_>>();
+        out.write_all(&bytes)?;
+    }
+
+    Ok(())
+}
+
+/// Creates the file at `path`, creating its directory if need be, and hands `write` a
+/// buffered writer for it.
+pub fn save(path: impl AsRef<Path>, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut out = BufWriter::new(File::create(path)?);
+    write(&mut out)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_netpbm() {
+        let grid = Grid::from_str("#.\n.#", |c| c == '#');
+
+        let mut out = Vec::new();
+        write_ascii(&mut out, &grid, |&b| if b { '#' } else { '.' }).unwrap();
+        assert_eq!(out, b"#.\n.#\n");
+
+        let mut out = Vec::new();
+        write_pbm(&mut out, &grid, 5, |&b| b).unwrap();
+        assert_eq!(out, b"P4\n10 10\n\xf8\x00\xf8\x00\xf8\x00\xf8\x00\xf8\x00\x07\xc0\x07\xc0\x07\xc0\x07\xc0\x07\xc0");
+
+        let mut out = Vec::new();
+        write_pgm(&mut out, &grid, 1, |&b| if b { 0 } else { 200 }).unwrap();
+        assert_eq!(out, b"P5\n2 2\n255\n\x00\xc8\xc8\x00");
+
+        let mut out = Vec::new();
+        write_ppm(&mut out, &grid, 1, |&b| if b { Rgb::RED } else { Rgb::gray(1) }).unwrap();
+        assert_eq!(out, b"P6\n2 2\n255\n\xff\x00\x00\x01\x01\x01\x01\x01\x01\xff\x00\x00");
+    }
+}
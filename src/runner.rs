@@ -0,0 +1,358 @@
+use std::env;
+use std::io::{self, Read};
+use std::path::Path;
+use std::process;
+use std::time::{Duration, Instant};
+use itertools::Itertools;
+
+/// A named implementation of one puzzle part.
+pub struct Strategy {
+    pub name: &'static str,
+    solver: Box<dyn Fn(&str) -> String>
+}
+
+impl Strategy {
+    pub fn run(&self, input: &str) -> (String, Duration) {
+        let start = Instant::now();
+        let result = (self.solver)(input);
+        (result, start.elapsed())
+    }
+}
+
+/// A puzzle part together with every strategy registered for it, in registration order.
+/// The strategies of a part of `variants` compute different things by design, such as
+/// alternative checksums, so they are not expected to agree, and only run when asked for by
+/// name.
+pub struct Part {
+    pub name: &'static str,
+    pub strategies: Vec<Strategy>,
+    pub variants: bool
+}
+
+impl Part {
+    pub fn strategy(&self, name: &str) -> Option<&Strategy> {
+        self.strategies.iter().find(|s| s.name == name)
+    }
+
+    /// The first registered strategy.
+    pub fn default_strategy(&self) -> &Strategy {
+        &self.strategies[0]
+    }
+}
+
+pub struct Timing {
+    pub name: &'static str,
+    pub result: String,
+    pub best: Duration,
+    pub mean: Duration
+}
+
+/// A strategy of a part of variants, which may fail on its own without stopping the others.
+pub type VariantFn = Box<dyn Fn(&str) -> Result<String, String>>;
+
+type RenderFn = dyn Fn(&str, &Path, &[&str]) -> io::Result<()>;
+
+/// Writes files worked out from the input, such as pictures or tables, into a directory,
+/// given any extra command line arguments.
+pub struct Renderer {
+    pub name: &'static str,
+    render: Box<RenderFn>
+}
+
+/// Works the answers out from a reader as it goes rather than from the whole input at once,
+/// given any extra command line arguments, returning each part's name and answer.
+type StreamFn = dyn Fn(&mut dyn Read, &[&str]) -> Result<Vec<(&'static str, String)>, String>;
+
+/// Works the answers out from the input, saving progress into a directory as it goes and
+/// resuming from whatever an earlier, interrupted run saved there.
+type CheckpointFn = dyn Fn(&str, &Path) -> Result<Vec<(&'static str, String)>, String>;
+
+#[derive(Default)]
+pub struct Registry {
+    parts: Vec<Part>,
+    renderers: Vec<Renderer>,
+    checkpoint: Option<Box<CheckpointFn>>,
+    stream: Option<Box<StreamFn>>
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry { parts: Vec::new(), renderers: Vec::new(), checkpoint: None, stream: None }
+    }
+
+    pub fn register<F>(&mut self, part: &'static str, name: &'static str, solver: F)
+    where F: Fn(&str) -> String + 'static
+    {
+        self.add(part, false, Strategy { name, solver: Box::new(solver) });
+    }
+
+    /// Registers a strategy of a part whose strategies each compute something different. An
+    /// error becomes that strategy's answer, so that the others still run.
+    pub fn register_variant<F>(&mut self, part: &'static str, name: &'static str, solver: F)
+    where F: Fn(&str) -> Result<String, String> + 'static
+    {
+        let solver = move |input: &str| solver(input).unwrap_or_else(|e| format!("error: {e}"));
+        self.add(part, true, Strategy { name, solver: Box::new(solver) });
+    }
+
+    fn add(&mut self, part: &'static str, variants: bool, strategy: Strategy) {
+        match self.parts.iter_mut().find(|p| p.name == part) {
+            Some(p) => p.strategies.push(strategy),
+            None => self.parts.push(Part { name: part, strategies: vec![strategy], variants })
+        }
+    }
+
+    pub fn register_renderer<F>(&mut self, name: &'static str, render: F)
+    where F: Fn(&str, &Path, &[&str]) -> io::Result<()> + 'static
+    {
+        self.renderers.push(Renderer { name, render: Box::new(render) });
+    }
+
+    pub fn register_checkpoint<F>(&mut self, checkpoint: F)
+    where F: Fn(&str, &Path) -> Result<Vec<(&'static str, String)>, String> + 'static
+    {
+        self.checkpoint = Some(Box::new(checkpoint));
+    }
+
+    pub fn register_stream<F>(&mut self, stream: F)
+    where F: Fn(&mut dyn Read, &[&str]) -> Result<Vec<(&'static str, String)>, String> + 'static
+    {
+        self.stream = Some(Box::new(stream));
+    }
+
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    pub fn renderers(&self) -> &[Renderer] {
+        &self.renderers
+    }
+
+    /// Runs each part with the named strategy, or with its default if it has none by that
+    /// name; a part of variants runs only if it has the named strategy. A name that no part
+    /// has is an error listing the strategies there are.
+    pub fn run(&self, input: &str, strategy: Option<&str>) -> Result<(), String> {
+        if let Some(name) = strategy {
+            if self.parts.iter().all(|part| part.strategy(name).is_none()) {
+                let names = self.parts.iter()
+                    .flat_map(|part| part.strategies.iter().map(|s| s.name))
+                    .unique()
+                    .collect::<Vec<_>>();
+                return Err(format!("unknown strategy {name:?}, expected one of {}", names.join(", ")));
+            }
+        }
+
+        for part in self.parts.iter() {
+            let strategy = match strategy.and_then(|name| part.strategy(name)) {
+                Some(strategy) => strategy,
+                None if part.variants => continue,
+                None => part.default_strategy()
+            };
+            let (result, duration) = strategy.run(input);
+            println!("{}: {} (Time: {}μs)", part.name, result, duration.as_micros());
+        }
+
+        Ok(())
+    }
+
+    /// Runs every strategy of `part` `iterations` times, returning an error naming each
+    /// strategy's answer if they do not all agree, unless the part is of variants.
+    pub fn compare_part(part: &Part, input: &str, iterations: usize) -> Result<Vec<Timing>, String> {
+        let timings = part.strategies.iter()
+            .map(|strategy| {
+                let runs = (0..iterations.max(1)).map(|_| strategy.run(input)).collect::<Vec<_>>();
+                let best = runs.iter().map(|(_, d)| *d).min().unwrap();
+                let mean = runs.iter().map(|(_, d)| *d).sum::<Duration>() / runs.len() as u32;
+                let result = runs.into_iter().next().unwrap().0;
+
+                Timing { name: strategy.name, result, best, mean }
+            })
+            .collect::<Vec<_>>();
+
+        if part.variants || timings.iter().all(|t| t.result == timings[0].result) {
+            Ok(timings)
+        }
+        else {
+            let answers = timings.iter()
+                .map(|t| format!("{} = {}", t.name, t.result))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            Err(format!("{}: strategies disagree ({answers})", part.name))
+        }
+    }
+
+    pub fn compare(&self, input: &str, iterations: usize) -> Result<(), String> {
+        for part in self.parts.iter() {
+            let timings = Registry::compare_part(part, input, iterations)?;
+            let width = timings.iter().map(|t| t.name.len()).max().unwrap_or(0);
+
+            if part.variants {
+                println!("{}:", part.name);
+            }
+            else {
+                println!("{}: {}", part.name, timings[0].result);
+            }
+
+            for Timing { name, result, best, mean } in timings.iter() {
+                let result = if part.variants { format!("  = {result}") } else { String::new() };
+                println!("  {name:width$}  best: {:>10}μs  mean: {:>10}μs{result}", best.as_micros(), mean.as_micros());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs every renderer, each writing into `dir`, passing each of them `args`.
+    pub fn render(&self, input: &str, dir: &Path, args: &[&str]) -> io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+
+        for renderer in self.renderers.iter() {
+            (renderer.render)(input, dir, args)?;
+            println!("{}: written to {}", renderer.name, dir.display());
+        }
+
+        Ok(())
+    }
+
+    /// Runs the checkpoint handler, keeping its checkpoints in `dir`.
+    pub fn checkpoint(&self, input: &str, dir: &Path) -> Result<(), String> {
+        let checkpoint = self.checkpoint.as_ref().ok_or("no checkpoint handler registered")?;
+
+        let start = Instant::now();
+        let results = checkpoint(input, dir)?;
+        let duration = start.elapsed();
+
+        for (part, result) in results {
+            println!("{part}: {result}");
+        }
+        println!("(Time: {}μs)", duration.as_micros());
+
+        Ok(())
+    }
+
+    /// Runs the stream handler over `reader`, in a single pass shared by every part.
+    pub fn stream(&self, reader: &mut dyn Read, args: &[&str]) -> Result<(), String> {
+        let stream = self.stream.as_ref().ok_or("no stream handler registered")?;
+
+        let start = Instant::now();
+        let results = stream(reader, args)?;
+        let duration = start.elapsed();
+
+        for (part, result) in results {
+            println!("{part}: {result}");
+        }
+        println!("(Time: {}μs)", duration.as_micros());
+
+        Ok(())
+    }
+
+    fn usage(&self) -> ! {
+        eprintln!("usage: [run [STRATEGY] | compare [ITERATIONS] | render [DIR [ARGS...]] | checkpoint DIR | stream [ARGS...] | list]");
+        process::exit(2)
+    }
+
+    /// Entry point used by `build_main!`: dispatches on the command line arguments.
+    pub fn main(&self, input: &str) {
+        let args = env::args().skip(1).collect::<Vec<_>>();
+        let args = args.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+
+        match args.as_slice() {
+            [] | ["run"] | ["run", _] => {
+                if let Err(e) = self.run(input, args.get(1).copied()) {
+                    eprintln!("{e}");
+                    self.usage();
+                }
+            },
+            ["compare", rest @ ..] => {
+                let iterations = match rest {
+                    [] => 1,
+                    [n] => n.parse::<usize>().unwrap_or_else(|_| self.usage()),
+                    _ => self.usage()
+                };
+
+                if let Err(e) = self.compare(input, iterations) {
+                    eprintln!("{e}");
+                    process::exit(1);
+                }
+            },
+            ["render", rest @ ..] => {
+                let (dir, args) = match rest {
+                    [] => ("output", rest),
+                    [dir, args @ ..] => (*dir, args)
+                };
+
+                if let Err(e) = self.render(input, Path::new(dir), args) {
+                    eprintln!("{e}");
+                    process::exit(1);
+                }
+            },
+            ["checkpoint", dir] => {
+                if let Err(e) = self.checkpoint(input, Path::new(dir)) {
+                    eprintln!("{e}");
+                    process::exit(1);
+                }
+            },
+            ["stream", rest @ ..] => {
+                if let Err(e) = self.stream(&mut io::stdin().lock(), rest) {
+                    eprintln!("{e}");
+                    process::exit(1);
+                }
+            },
+            ["list"] => {
+                for part in self.parts.iter() {
+                    let names = part.strategies.iter().map(|s| s.name).collect::<Vec<_>>();
+                    let kind = if part.variants { " (variants, run by name)" } else { "" };
+                    println!("{}{kind}: {}", part.name, names.join(", "));
+                }
+                if !self.renderers.is_empty() {
+                    let names = self.renderers.iter().map(|r| r.name).collect::<Vec<_>>();
+                    println!("Renderers: {}", names.join(", "));
+                }
+                if self.checkpoint.is_some() {
+                    println!("Checkpoint: saves progress into a directory and resumes from it");
+                }
+                if self.stream.is_some() {
+                    println!("Stream: reads the input from stdin");
+                }
+            },
+            _ => self.usage()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let mut registry = Registry::new();
+        registry.register("Part 1", "chars", |input| input.chars().count().to_string());
+        registry.register("Part 1", "bytes", |input| input.len().to_string());
+        registry.register("Part 2", "first", |input| input[..1].to_string());
+
+        assert_eq!(registry.parts().len(), 2);
+        assert_eq!(registry.parts()[0].strategy("bytes").map(|s| s.name), Some("bytes"));
+        assert!(registry.parts()[0].strategy("missing").is_none());
+        assert_eq!(registry.parts()[0].default_strategy().name, "chars");
+        assert!(registry.run("abc", Some("first")).is_ok());
+        assert_eq!(registry.run("abc", Some("missing")), Err("unknown strategy \"missing\", expected one of chars, bytes, first".to_string()));
+
+        let timings = Registry::compare_part(&registry.parts()[0], "abc", 3).unwrap();
+        assert_eq!(timings.len(), 2);
+        assert!(timings.iter().all(|t| t.result == "3"));
+
+        assert!(Registry::compare_part(&registry.parts()[0], "ä", 1).is_err());
+
+        registry.register_variant("Length", "chars", |input| Ok(input.chars().count().to_string()));
+        registry.register_variant("Length", "bytes", |input| Ok(input.len().to_string()));
+        registry.register_variant("Length", "ascii", |input| match input.is_ascii() {
+            true => Ok(input.len().to_string()),
+            false => Err("not ascii".to_string())
+        });
+        let timings = Registry::compare_part(&registry.parts()[2], "ä", 1).unwrap();
+        assert_eq!(timings.iter().map(|t| t.result.as_str()).collect::<Vec<_>>(), vec!["1", "2", "error: not ascii"]);
+        assert!(registry.run("abc", Some("ascii")).is_ok());
+    }
+}
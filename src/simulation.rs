@@ -0,0 +1,128 @@
+/// A system that advances one discrete step at a time.
+pub trait Simulation {
+    /// What a single step reports.
+    type Output;
+
+    /// Everything needed to rewind to the current point, the step counter included.
+    type Snapshot;
+
+    /// Advances one step, or returns `None` and leaves the state alone once finished.
+    fn step(&mut self) -> Option<Self::Output>;
+
+    /// The number of steps taken so far.
+    fn steps(&self) -> usize;
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    fn restore(&mut self, snapshot: Self::Snapshot);
+
+    /// Takes up to `n` steps, returning the last output.
+    fn run_for(&mut self, n: usize) -> Option<Self::Output> {
+        let mut last = None;
+        for _ in 0..n {
+            match self.step() {
+                Some(output) => last = Some(output),
+                None => break
+            }
+        }
+        last
+    }
+
+    /// Steps until `done` accepts an output, returning it, or `None` if the simulation
+    /// finishes first.
+    fn run_until(&mut self, mut done: impl FnMut(&Self, &Self::Output) -> bool) -> Option<Self::Output>
+    where Self: Sized
+    {
+        while let Some(output) = self.step() {
+            if done(self, &output) {
+                return Some(output);
+            }
+        }
+        None
+    }
+
+    /// Steps until finished, returning the last output. Never returns for a simulation
+    /// without an end.
+    fn run(&mut self) -> Option<Self::Output> {
+        let mut last = None;
+        while let Some(output) = self.step() {
+            last = Some(output);
+        }
+        last
+    }
+
+    /// The remaining outputs, one per step.
+    fn outputs(&mut self) -> Outputs<'_, Self>
+    where Self: Sized
+    {
+        Outputs(self)
+    }
+}
+
+pub struct Outputs<'a, S>(&'a mut S);
+
+impl<S: Simulation> Iterator for Outputs<'_, S> {
+    type Item = S::Output;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts down to zero, reporting each value reached.
+    struct Countdown {
+        value: usize,
+        steps: usize
+    }
+
+    impl Simulation for Countdown {
+        type Output = usize;
+        type Snapshot = (usize, usize);
+
+        fn step(&mut self) -> Option<usize> {
+            if self.value == 0 {
+                return None;
+            }
+
+            self.value -= 1;
+            self.steps += 1;
+            Some(self.value)
+        }
+
+        fn steps(&self) -> usize {
+            self.steps
+        }
+
+        fn snapshot(&self) -> (usize, usize) {
+            (self.value, self.steps)
+        }
+
+        fn restore(&mut self, (value, steps): (usize, usize)) {
+            self.value = value;
+            self.steps = steps;
+        }
+    }
+
+    #[test]
+    fn test_simulation() {
+        let mut sim = Countdown { value: 10, steps: 0 };
+        assert_eq!(sim.run_for(3), Some(7));
+        assert_eq!(sim.run_until(|_, &v| v % 5 == 0), Some(5));
+        assert_eq!(sim.steps(), 5);
+
+        let snapshot = sim.snapshot();
+        assert_eq!(sim.outputs().take(2).collect::<Vec<_>>(), vec![4, 3]);
+        assert_eq!(sim.run(), Some(0));
+        assert_eq!(sim.step(), None);
+        assert_eq!(sim.run_for(5), None);
+
+        sim.restore(snapshot);
+        assert_eq!((sim.value, sim.steps()), (5, 5));
+        assert_eq!(sim.run_until(|_, &v| v > 10), None);
+        assert_eq!(sim.steps(), 10);
+    }
+}
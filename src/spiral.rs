@@ -0,0 +1,163 @@
+use crate::geom::Point2;
+use crate::math::isqrt;
+
+/// The squares of the spiral, numbered from 1 at the origin and winding anticlockwise
+/// outwards from `(1, 0)`, with `y` increasing upwards, as `(number, position)`.
+pub struct RingIter {
+    i: usize,
+    pos: Point2,
+    delta: Point2
+}
+
+impl RingIter {
+    pub fn new() -> RingIter {
+        RingIter { i: 1, pos: Point2(0, 0), delta: Point2(0, -1) }
+    }
+}
+
+impl Iterator for RingIter {
+    type Item = (usize, Point2);
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = Some((self.i, self.pos));
+
+        let Point2(x, y) = self.pos;
+        if x == y || (x < 0 && y == -x) || (x > 0 && x == 1 - y ){
+            self.delta = self.delta.rotate_left();
+        }
+
+        self.pos += self.delta;
+        self.i += 1;
+
+        result
+    }
+}
+
+impl Default for RingIter {
+    fn default() -> RingIter {
+        RingIter::new()
+    }
+}
+
+/// Where square `n` lies, without walking the spiral. Ring `k` is the square of side `2k + 1`
+/// centred on the origin; it holds the squares after `(2k - 1)^2` up to `(2k + 1)^2`, starting
+/// just above its bottom right corner and running anticlockwise.
+pub fn square_position(n: usize) -> Point2 {
+    assert!(n > 0, "squares are numbered from 1");
+    if n == 1 {
+        return Point2(0, 0);
+    }
+
+    let k = (isqrt(n as u64 - 1) as usize).div_ceil(2);
+    let m = n - (2 * k - 1).pow(2) - 1;
+    let (side, t) = ((m / (2 * k)) as isize, (m % (2 * k)) as isize);
+    let k = k as isize;
+
+    match side {
+        0 => Point2(k, 1 - k + t),
+        1 => Point2(k - 1 - t, k),
+        2 => Point2(-k, k - 1 - t),
+        _ => Point2(1 - k + t, -k)
+    }
+}
+
+/// The number of the square at `pos`; the inverse of `square_position`.
+pub fn square_number(pos: Point2) -> usize {
+    let Point2(x, y) = pos;
+    let k = pos.chebyshev();
+    if k == 0 {
+        return 1;
+    }
+
+    let m = if x == k && y > -k {
+        y + k - 1
+    }
+    else if y == k {
+        3 * k - 1 - x
+    }
+    else if x == -k {
+        5 * k - 1 - y
+    }
+    else {
+        7 * k - 1 + x
+    };
+
+    (2 * k - 1).pow(2) as usize + 1 + m as usize
+}
+
+/// The stress test sequence: square 1 holds 1, and every later square the sum of the
+/// neighbouring squares, diagonals included, already filled in. Yields `(number, position,
+/// value)`, or an error once a value no longer fits in a `usize`, after which it ends.
+pub struct StressTest {
+    squares: RingIter,
+    /// Every value so far, indexed by square number, so that a square's neighbours are found
+    /// with `square_number` instead of a hash lookup.
+    values: Vec<usize>,
+    overflowed: bool
+}
+
+impl StressTest {
+    pub fn new() -> StressTest {
+        StressTest { squares: RingIter::new(), values: vec![0], overflowed: false }
+    }
+}
+
+impl Default for StressTest {
+    fn default() -> StressTest {
+        StressTest::new()
+    }
+}
+
+impl Iterator for StressTest {
+    type Item = Result<(usize, Point2, usize), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.overflowed {
+            return None;
+        }
+
+        let (i, pos) = self.squares.next()?;
+        let value = if i == 1 {
+            Some(1)
+        }
+        else {
+            pos.neighbors8().into_iter()
+                .map(square_number)
+                .filter(|&j| j < i)
+                .try_fold(0usize, |sum, j| sum.checked_add(self.values[j]))
+        };
+
+        match value {
+            Some(value) => {
+                self.values.push(value);
+                Some(Ok((i, pos, value)))
+            },
+            None => {
+                self.overflowed = true;
+                Some(Err(format!("stress test value for square {i} overflows")))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_position() {
+        for (n, pos) in RingIter::new().take(1_000_000) {
+            assert_eq!(square_position(n), pos, "square {n}");
+            assert_eq!(square_number(pos), n, "position {pos:?}");
+        }
+    }
+
+    #[test]
+    fn test_stress_test() {
+        let values = StressTest::new().take(12).map(|r| r.unwrap().2).collect::<Vec<_>>();
+        assert_eq!(values, vec![1, 1, 2, 4, 5, 10, 11, 23, 25, 26, 54, 57]);
+
+        let mut results = StressTest::new().skip_while(|r| r.is_ok());
+        assert!(results.next().unwrap().unwrap_err().contains("overflows"));
+        assert!(results.next().is_none());
+    }
+}